@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rscalc::{parse, tokenize, Interpreter, Variant};
+
+// Evaluates one formula over many points, with the interpreter and compiled
+fn eval_formula(c: &mut Criterion) {
+    let mut interpreter = Interpreter::<f64>::default();
+    let tokens = tokenize("f(x) = 3x^2 - 2x + 1").unwrap();
+    interpreter.eval(&parse(&tokens).unwrap()).unwrap();
+    let tokens = tokenize("x < 0 ? f(-x) : sin(x) * f(x) + sqrt(x + y) / (1 + y^2)").unwrap();
+    let expr = parse(&tokens).unwrap();
+    let compiled = interpreter.compile(&expr, &["x", "y"]).unwrap();
+    let points: Vec<(f64, f64)> = (0..1000)
+        .map(|i| (f64::from(i) / 100.0 - 5.0, f64::from(i % 7)))
+        .collect();
+
+    let mut group = c.benchmark_group("1000 points");
+    group.bench_function("Interpreter::eval", |b| {
+        b.iter(|| {
+            for &(x, y) in &points {
+                interpreter.set_var(String::from("x"), Variant::Num(x));
+                interpreter.set_var(String::from("y"), Variant::Num(y));
+                black_box(interpreter.eval(&expr).unwrap());
+            }
+        })
+    });
+    group.bench_function("CompiledExpr::eval", |b| {
+        b.iter(|| {
+            for &(x, y) in &points {
+                black_box(compiled.eval(&[x, y]).unwrap());
+            }
+        })
+    });
+    group.bench_function("CompiledExpr::eval_with", |b| {
+        let mut scratch = Vec::new();
+        b.iter(|| {
+            for &(x, y) in &points {
+                black_box(compiled.eval_with(&[x, y], &mut scratch).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, eval_formula);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+use crate::integer::register_int_builtins;
+use crate::{Interpreter, Num, NumError};
+use num_bigint::BigInt;
+use num_traits::Signed;
+
+// Larger results than this many bits are reported as overflow instead of exhausting memory
+const MAX_POW_BITS: u64 = 1 << 20;
+
+impl Num for BigInt {
+    #[inline(always)]
+    fn zero() -> Self {
+        BigInt::ZERO
+    }
+    #[inline(always)]
+    fn one() -> Self {
+        BigInt::from(1)
+    }
+    #[inline(always)]
+    fn is_whole(&self) -> bool {
+        true
+    }
+    // This panics on negative or huge exponents, the interpreter uses try_pow
+    fn pow(self, other: Self) -> Self {
+        self.try_pow(other).expect("exponent out of range")
+    }
+
+    fn try_neg(self) -> Result<Self, NumError> {
+        Ok(-self)
+    }
+    fn try_div(self, other: Self) -> Result<Self, NumError> {
+        if other == BigInt::ZERO {
+            return Err(NumError::Domain);
+        }
+        Ok(self / other)
+    }
+    fn try_rem(self, other: Self) -> Result<Self, NumError> {
+        if other == BigInt::ZERO {
+            return Err(NumError::Domain);
+        }
+        Ok(self % other)
+    }
+    fn try_pow(self, other: Self) -> Result<Self, NumError> {
+        let exponent = u32::try_from(&other).map_err(|_| NumError::Domain)?;
+        // 0, 1 and -1 stay small no matter the exponent
+        if self.abs() > BigInt::from(1) && self.bits() * u64::from(exponent) > MAX_POW_BITS {
+            return Err(NumError::Overflow);
+        }
+        Ok(BigInt::pow(&self, exponent))
+    }
+}
+
+impl Default for Interpreter<BigInt> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        register_int_builtins(&mut interpreter.vars);
+        interpreter
+    }
+}
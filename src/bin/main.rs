@@ -4,11 +4,109 @@ use rustyline::{Config, DefaultEditor, EditMode};
 use structopt::StructOpt;
 
 use rscalc::{
-    parse, tokenize, InterpretError, Interpreter, Num, ParseError, ParseErrorCode, TokenizeError,
-    Variant,
+    derive, parse, simplify, to_latex, to_mathml, tokenize, AngleMode, BigInt, Complex, Decimal,
+    Error, Expr, Interpreter, Interval, Num, Quantity, Rational, Unsigned, Variant,
 };
 use std::fmt::Display;
-use std::ops::Range;
+use std::str::FromStr;
+
+#[derive(Copy, Clone)]
+enum Radix {
+    Hex,
+    Bin,
+    Oct,
+}
+
+impl FromStr for Radix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(Radix::Hex),
+            "bin" => Ok(Radix::Bin),
+            "oct" => Ok(Radix::Oct),
+            _ => Err(format!("unknown radix {:?}, expected hex, bin or oct", s)),
+        }
+    }
+}
+
+impl Radix {
+    // Formats whole results only, using their decimal text so it works for any Num
+    fn format(self, decimal: &str) -> Option<String> {
+        let n = decimal.parse::<i128>().ok()?;
+        let sign = if n < 0 { "-" } else { "" };
+        let n = n.unsigned_abs();
+        Some(match self {
+            Radix::Hex => format!("{}0x{:x}", sign, n),
+            Radix::Bin => format!("{}0b{:b}", sign, n),
+            Radix::Oct => format!("{}0o{:o}", sign, n),
+        })
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Render {
+    Latex,
+    MathML,
+}
+
+impl FromStr for Render {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latex" => Ok(Render::Latex),
+            "mathml" => Ok(Render::MathML),
+            _ => Err(format!("unknown format {:?}, expected latex or mathml", s)),
+        }
+    }
+}
+
+impl Render {
+    fn render<N: Num + Display>(self, expr: &Expr<N>) -> String {
+        match self {
+            Render::Latex => to_latex(expr),
+            Render::MathML => to_mathml(expr),
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Backend {
+    Float,
+    Decimal,
+    Complex,
+    I64,
+    I128,
+    U64,
+    BigInt,
+    Rational,
+    Interval,
+    Quantity,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "f64" => Ok(Backend::Float),
+            "decimal" => Ok(Backend::Decimal),
+            "complex" => Ok(Backend::Complex),
+            "i64" => Ok(Backend::I64),
+            "i128" => Ok(Backend::I128),
+            "u64" => Ok(Backend::U64),
+            "bigint" => Ok(Backend::BigInt),
+            "rational" => Ok(Backend::Rational),
+            "interval" => Ok(Backend::Interval),
+            "units" => Ok(Backend::Quantity),
+            _ => Err(format!(
+                "unknown backend {:?}, expected f64, decimal, complex, i64, i128, u64, bigint, rational, interval or units",
+                s
+            )),
+        }
+    }
+}
 
 #[derive(StructOpt)]
 #[structopt(about = "A scientific calculator for the terminal.")]
@@ -27,26 +125,148 @@ struct Opt {
     emacs: bool,
     #[structopt(short = "V", long = "vi", help = "Use Vi key bindings")]
     vim: bool,
+    #[structopt(
+        short = "r",
+        long = "radix",
+        help = "Also prints whole results in hex, bin or oct",
+        possible_values = &["hex", "bin", "oct"]
+    )]
+    radix: Option<Radix>,
+    #[structopt(
+        short = "m",
+        long = "render",
+        help = "Prints expressions as LaTeX or MathML, instead of evaluating an expression argument",
+        possible_values = &["latex", "mathml"]
+    )]
+    render: Option<Render>,
+    #[structopt(long = "strict", help = "Fails instead of returning NaN or infinity")]
+    strict: bool,
+    #[structopt(
+        short = "a",
+        long = "angle",
+        help = "Angle mode of trigonometric functions",
+        default_value = "rad",
+        possible_values = &["rad", "deg", "grad"]
+    )]
+    angle: AngleMode,
+    #[structopt(
+        short = "b",
+        long = "backend",
+        help = "Number type used for evaluation",
+        default_value = "f64",
+        possible_values = &["f64", "decimal", "complex", "i64", "i128", "u64", "bigint", "rational", "interval", "units"]
+    )]
+    backend: Backend,
+    #[structopt(long = "polar", help = "Prints complex results in polar form")]
+    polar: bool,
+}
+
+// How results of each backend are printed
+trait Output: Num + Display {
+    fn format(&self, _opt: &Opt, _angle_mode: AngleMode) -> String {
+        self.to_string()
+    }
+}
+
+impl Output for f64 {}
+
+impl Output for Decimal {
+    // Results keep the trailing zeros of their inputs, like 2^-1 giving 0.50
+    fn format(&self, _opt: &Opt, _angle_mode: AngleMode) -> String {
+        self.normalize().to_string()
+    }
+}
+
+impl Output for i64 {}
+
+impl Output for i128 {}
+
+impl Output for Unsigned {}
+
+impl Output for BigInt {}
+
+impl Output for Rational {
+    // Fractions are followed by their decimal value
+    fn format(&self, _opt: &Opt, _angle_mode: AngleMode) -> String {
+        if self.is_whole() {
+            return self.to_string();
+        }
+        match self.to_decimal(16) {
+            (decimal, true) => format!("{} = {}", self, decimal),
+            (decimal, false) => format!("{} ≈ {}", self, decimal),
+        }
+    }
+}
+
+impl Output for Interval {
+    // Wide intervals are printed as their midpoint and radius, followed by their bounds
+    fn format(&self, _opt: &Opt, _angle_mode: AngleMode) -> String {
+        if self.is_point() {
+            return self.to_string();
+        }
+        let (mid, radius) = (
+            Interval::point(self.midpoint()),
+            Interval::point(self.radius()),
+        );
+        format!("{} ± {} {}", mid, radius, self)
+    }
+}
+
+impl Output for Quantity {}
+
+impl Output for Complex {
+    fn format(&self, opt: &Opt, angle_mode: AngleMode) -> String {
+        if !opt.polar {
+            return self.to_string();
+        }
+        let unit = match angle_mode {
+            AngleMode::Radians => "",
+            AngleMode::Degrees => "°",
+            AngleMode::Gradians => " grad",
+        };
+        format!(
+            "{}∠{}{}",
+            self.abs(),
+            angle_mode.from_radians(self.arg()),
+            unit
+        )
+    }
 }
 
 fn main() {
     let opt = Opt::from_args();
+    match opt.backend {
+        Backend::Float => run(opt, Interpreter::<f64>::default()),
+        Backend::Decimal => run(opt, Interpreter::<Decimal>::default()),
+        Backend::Complex => run(opt, Interpreter::<Complex>::default()),
+        Backend::I64 => run(opt, Interpreter::<i64>::default()),
+        Backend::I128 => run(opt, Interpreter::<i128>::default()),
+        Backend::U64 => run(opt, Interpreter::<Unsigned>::default()),
+        Backend::BigInt => run(opt, Interpreter::<BigInt>::default()),
+        Backend::Rational => run(opt, Interpreter::<Rational>::default()),
+        Backend::Interval => run(opt, Interpreter::<Interval>::default()),
+        Backend::Quantity => run(opt, Interpreter::<Quantity>::default()),
+    }
+}
 
-    let mut interpreter = Interpreter::default();
+fn run<N: Output>(mut opt: Opt, mut interpreter: Interpreter<N>) {
+    interpreter.strict = opt.strict;
+    interpreter.angle_mode = opt.angle;
 
-    if let Some(expr) = opt.expr {
-        match tokenize(&expr) {
-            Ok(tokens) => match parse(&tokens) {
-                Ok(expr) => match interpreter.eval(&expr) {
-                    Ok(result) => {
-                        println!("{}", result);
-                        return;
-                    }
-                    Err(e) => eprintln!("{:?}", e),
-                },
-                Err(ParseError { code, span }) => eprintln!("{:?} at {:?}", code, span),
-            },
-            Err(TokenizeError { code, span }) => eprintln!("{:?} at {:?}", code, span),
+    if let Some(expr) = &opt.expr {
+        let output = match opt.render {
+            // rendering does not need the variables of the expression to be defined
+            Some(render) => parse_str::<N>(expr).map(|expr| render.render(&expr)),
+            None => interpreter
+                .eval_str(expr)
+                .map(|result| format_result(&result, &opt, interpreter.angle_mode)),
+        };
+        match output {
+            Ok(output) => {
+                println!("{}", output);
+                return;
+            }
+            Err(err) => eprintln!("{}", err),
         }
         std::process::exit(1);
     }
@@ -94,27 +314,91 @@ fn main() {
         } else if &buffer[..] == "clear" {
             print!("\x1Bc");
             continue;
+        } else if let Some(radix) = buffer.strip_prefix("radix ") {
+            match radix.trim() {
+                "off" => opt.radix = None,
+                radix => match radix.parse() {
+                    Ok(radix) => opt.radix = Some(radix),
+                    Err(err) => println!(
+                        "{}",
+                        if opt.no_color {
+                            err
+                        } else {
+                            err.red().to_string()
+                        }
+                    ),
+                },
+            }
+        } else if let Some(render) = buffer.strip_prefix("render ") {
+            match render.trim() {
+                "off" => opt.render = None,
+                render => match render.parse() {
+                    Ok(render) => opt.render = Some(render),
+                    Err(err) => println!(
+                        "{}",
+                        if opt.no_color {
+                            err
+                        } else {
+                            err.red().to_string()
+                        }
+                    ),
+                },
+            }
+        } else if &buffer[..] == "angle" {
+            println!("{:?}", interpreter.angle_mode);
+        } else if let Some(mode) = buffer.strip_prefix("angle ") {
+            match mode.trim().parse() {
+                Ok(mode) => interpreter.angle_mode = mode,
+                Err(err) => println!(
+                    "{}",
+                    if opt.no_color {
+                        err
+                    } else {
+                        err.red().to_string()
+                    }
+                ),
+            }
+        } else if let Some(derivative) = buffer.strip_prefix("derive ") {
+            print_derivative::<N>(derivative, opt.no_color);
+        } else if let Some(input) = buffer.strip_prefix("simplify ") {
+            if let Some(expr) = parse_or_print_error::<N>(input, opt.no_color) {
+                print_expr(&simplify(&expr), opt.no_color);
+            }
+        } else if &buffer[..] == "polar" {
+            opt.polar = true;
+        } else if &buffer[..] == "rect" {
+            opt.polar = false;
         } else if buffer.starts_with(":") {
             continue;
         } else {
-            evaluate(
-                &buffer,
-                &mut interpreter,
-                opt.tokens,
-                opt.bexpr,
-                opt.vars,
-                opt.no_color,
-                ":",
-            );
+            evaluate(&buffer, &mut interpreter, &opt, ":");
         }
     }
 }
 
-const COMMANDS: [(&str, &str); 5] = [
+const COMMANDS: [(&str, &str); 11] = [
     ("quit|exit", "Close RSCALC"),
     ("help", "Show this help information"),
     ("vars", "Display all of the active variables"),
     ("clear", "Clear prior output"),
+    ("radix", "Also show results in hex, bin or oct (or off)"),
+    (
+        "render",
+        "Also show expressions as latex or mathml (or off)",
+    ),
+    ("angle", "Show or set the angle mode (rad, deg or grad)"),
+    (
+        "derive",
+        "Print the derivative of an expression, like derive x x^2",
+    ),
+    (
+        "simplify",
+        "Print an expression with constants folded and like terms combined",
+    ),
+    (
+        "polar|rect",
+        "Print complex results in polar or rectangular form",
+    ),
     (":", "Write notes"),
 ];
 
@@ -135,13 +419,23 @@ fn print_help(no_color: bool) {
     println!("\t12.3(0.7)");
     println!("\t|-9| + 3!");
     println!("\tx = abs(-5)");
+    println!("\t2*y + 3 = 11");
+    println!("\tf(x, y) = x^2 + y");
+    println!("\t0xff + 0b1");
+    println!("\tsin(90°)");
     println!("\t-x^4");
+    println!("\tf(x) = x < 0 ? 0 : x");
+    println!("\tsigma(i, 1, 10, i^2)");
 }
 
 fn get_variant_ord<N: Num>(v: &Variant<N>) -> usize {
     match v {
-        Variant::Num(_) => 1,
-        Variant::Function(_) => 0,
+        Variant::Num(_) => 2,
+        Variant::UserFunction(..) => 1,
+        Variant::Function(_)
+        | Variant::AngleFunction(_)
+        | Variant::LazyFunction(_)
+        | Variant::Closure(_) => 0,
     }
 }
 
@@ -154,19 +448,25 @@ fn print_vars<N: Num + Display>(interpreter: &Interpreter<N>, no_color: bool) {
         v1_val.cmp(&v2_val)
     });
     for (id, val) in vars {
-        let fmt;
-        match val {
-            Variant::Num(n) => {
-                fmt = format!(
-                    "{} = {}",
-                    if no_color { id.normal() } else { id.green() },
-                    n.clone()
-                )
-            }
-            Variant::Function(_) => {
-                fmt = format!("{}(..)", if no_color { id.normal() } else { id.green() })
+        let fmt = match val {
+            Variant::Num(n) => format!(
+                "{} = {}",
+                if no_color { id.normal() } else { id.green() },
+                n.clone()
+            ),
+            Variant::Function(_)
+            | Variant::AngleFunction(_)
+            | Variant::LazyFunction(_)
+            | Variant::Closure(_) => {
+                format!("{}(..)", if no_color { id.normal() } else { id.green() })
             }
-        }
+            Variant::UserFunction(params, body) => format!(
+                "{}({}) = {}",
+                if no_color { id.normal() } else { id.green() },
+                params.join(", "),
+                body
+            ),
+        };
         println!(
             "{}",
             if no_color {
@@ -178,24 +478,73 @@ fn print_vars<N: Num + Display>(interpreter: &Interpreter<N>, no_color: bool) {
     }
 }
 
-fn format_error(span: Range<usize>, message: &str) -> String {
-    format!(
-        " {}{} {}",
-        " ".repeat(span.start),
-        "^".repeat(span.len()).red(),
-        message.red()
-    )
+fn format_error(err: Error, no_color: bool) -> String {
+    let (marker, message) = ("^".repeat(err.span.len()), err.kind.to_string());
+    if no_color {
+        format!(" {}{} {}", " ".repeat(err.span.start), marker, message)
+    } else {
+        format!(
+            " {}{} {}",
+            " ".repeat(err.span.start),
+            marker.red(),
+            message.red()
+        )
+    }
+}
+
+// `derive x x^2 + 1` prints the derivative of x^2 + 1 with respect to x
+fn print_derivative<N: Output>(input: &str, no_color: bool) {
+    let (var, input) = input.trim().split_once(' ').unwrap_or((input.trim(), ""));
+    let Some(expr) = parse_or_print_error::<N>(input, no_color) else {
+        return;
+    };
+    match derive(&expr, var) {
+        Ok(derivative) => print_expr(&simplify(&derivative), no_color),
+        Err(err) => {
+            let fmt = err.to_string();
+            println!("{}", if no_color { fmt } else { fmt.red().to_string() });
+        }
+    }
 }
 
-fn evaluate<N: Num + Display>(
+fn parse_str<N: Num>(input: &str) -> Result<Expr<N>, Error> {
+    let tokens = tokenize::<N>(input)?;
+    parse(&tokens).map_err(|err| Error::from_parse(err, input))
+}
+
+fn parse_or_print_error<N: Num>(input: &str, no_color: bool) -> Option<Expr<N>> {
+    match parse_str(input) {
+        Ok(expr) => Some(expr),
+        Err(err) => {
+            println!("{}", format_error(err, no_color));
+            None
+        }
+    }
+}
+
+fn print_expr<N: Num + Display>(expr: &Expr<N>, no_color: bool) {
+    let fmt = expr.to_string();
+    println!("{}", if no_color { fmt.normal() } else { fmt.green() });
+}
+
+fn format_result<N: Output>(result: &N, opt: &Opt, angle_mode: AngleMode) -> String {
+    let formatted = result.format(opt, angle_mode);
+    match opt
+        .radix
+        .and_then(|radix| radix.format(&result.to_string()))
+    {
+        Some(converted) => format!("{} ({})", formatted, converted),
+        None => formatted,
+    }
+}
+
+fn evaluate<N: Output>(
     input: &str,
     interpreter: &mut Interpreter<N>,
-    btokens: bool,
-    bexpr: bool,
-    bvars: bool,
-    bno_color: bool,
+    opt: &Opt,
     success_prefix: &str,
 ) {
+    let (btokens, bexpr, bvars, bno_color) = (opt.tokens, opt.bexpr, opt.vars, opt.no_color);
     match tokenize(input) {
         Ok(tokens) => {
             if btokens {
@@ -223,9 +572,24 @@ fn evaluate<N: Num + Display>(
                         );
                     }
 
-                    match interpreter.eval(&expr) {
+                    if let Some(render) = opt.render {
+                        println!("{}", render.render(&expr));
+                    }
+
+                    match interpreter.eval_spanned(&expr) {
                         Ok(result) => {
-                            // println!("{}{}", success_prefix, result);
+                            // definitions have no meaningful result, so echo the normalized definition
+                            let result = match &expr {
+                                Expr::FuncDef(id, ..)
+                                    if matches!(
+                                        interpreter.vars.get(id),
+                                        Some(Variant::UserFunction(..))
+                                    ) =>
+                                {
+                                    expr.to_string()
+                                }
+                                _ => format_result(&result, opt, interpreter.angle_mode),
+                            };
                             println!(
                                 "{} {}",
                                 if bno_color {
@@ -236,34 +600,13 @@ fn evaluate<N: Num + Display>(
                                 result
                             );
                         }
-                        Err(err) => {
-                            let fmt = format!("{}", display_interpret_error(&err));
-                            println!(
-                                "{}",
-                                if bno_color {
-                                    fmt
-                                } else {
-                                    fmt.red().to_string()
-                                }
-                            );
-                        }
-                    }
-                }
-                Err(ParseError { code, span }) => {
-                    if code == ParseErrorCode::UnexpectedEOF {
-                        println!(
-                            "{}",
-                            format_error(input.len()..input.len() + 1, &format!("{:?}", code))
-                        );
-                    } else {
-                        println!("{}", format_error(span, &format!("{:?}", code)));
+                        Err(err) => println!("{}", format_error(err, bno_color)),
                     }
                 }
+                Err(err) => println!("{}", format_error(Error::from_parse(err, input), bno_color)),
             }
         }
-        Err(TokenizeError { code, span }) => {
-            println!("{}", format_error(span, &format!("{:?}", code)));
-        }
+        Err(err) => println!("{}", format_error(err.into(), bno_color)),
     }
     if bvars {
         for (id, variant) in &interpreter.vars {
@@ -284,37 +627,3 @@ fn evaluate<N: Num + Display>(
         }
     }
 }
-
-#[inline(always)]
-fn s_if(b: bool) -> &'static str {
-    if b {
-        "s"
-    } else {
-        ""
-    }
-}
-
-fn display_interpret_error(err: &InterpretError) -> String {
-    match err {
-        InterpretError::TooFewArgs(id, n) => format!(
-            "Function {:?} did not receive minimum of {} argument{}.",
-            id,
-            n,
-            s_if(*n != 1)
-        ),
-        InterpretError::TooManyArgs(id, n) => format!(
-            "Function {:?} received more than the maximum {} argument{}.",
-            id,
-            n,
-            s_if(*n != 1)
-        ),
-        InterpretError::VarDoesNotExist(id) => format!("No variable or function {:?} exists.", id),
-        InterpretError::VarIsNotFunction(id) => format!(
-            "The variable {:?} cannot be used like a function with arguments.",
-            id
-        ),
-        InterpretError::FunctionNameUsedLikeVar(id) => {
-            format!("The function {:?} cannot be used without arguments.", id)
-        }
-    }
-}
@@ -0,0 +1,399 @@
+use crate::interpreter::truth;
+use crate::{
+    ensure_arg_count, AngleMode, ClosureFn, Expr, InterpretError, Interpreter, Num, OpVal, Variant,
+};
+use std::sync::Arc;
+
+// An expression compiled for a fixed set of variables, which evaluates without looking up names.
+// Made by Interpreter::compile.
+#[derive(Clone)]
+pub struct CompiledExpr<N: Num> {
+    code: Vec<Instr<N>>,
+    params: usize,
+    locals: usize,
+    depth: usize, // The most values the program has on its stack at once
+    strict: bool,
+}
+
+// The program is a stack machine: values are pushed, and operators and calls replace their operands
+#[derive(Clone)]
+enum Instr<N: Num> {
+    Num(N),
+    Arg(usize),   // Pushes an argument of eval
+    Local(usize), // Pushes a parameter of an inlined user function
+    Store(usize), // Pops into a parameter of an inlined user function
+    Neg,
+    Op(OpVal),
+    Truth, // Replaces a value with 1 if it is not 0, for the result of && and ||
+    Call(Callee<N>, usize, String), // Function, arg count, id
+    Jump(usize),
+    JumpUnless(usize), // Pops a condition and jumps if it is 0
+}
+
+#[derive(Clone)]
+enum Callee<N: Num> {
+    Function(for<'expr> fn(&'expr str, &[N]) -> Result<N, InterpretError<'expr>>),
+    AngleFunction(
+        for<'expr> fn(&'expr str, &[N], AngleMode) -> Result<N, InterpretError<'expr>>,
+        AngleMode,
+    ),
+    Closure(Arc<ClosureFn<N>>),
+}
+
+#[derive(Clone, Copy)]
+enum Slot {
+    Arg(usize),
+    Local(usize),
+}
+
+struct Compiler<'i, N: Num> {
+    interpreter: &'i Interpreter<N>,
+    code: Vec<Instr<N>>,
+    // Variables in reach, where later ones shadow earlier ones like the params of user functions do
+    scope: Vec<(String, Slot)>,
+    locals: usize,
+    // Values on the stack at the end of the code so far, and the most there have been
+    depth: usize,
+    max_depth: usize,
+    // User functions being inlined, to refuse recursion
+    calls: Vec<String>,
+}
+
+impl<N: Num> Interpreter<N> {
+    // Compiles expr for evaluating it many times with different values of params.
+    // Other variables and functions are looked up now, so later changes to them are not seen,
+    // and user functions are inlined. Definitions, recursive user functions and lazy functions
+    // cannot be compiled.
+    pub fn compile<'expr>(
+        &self,
+        expr: &'expr Expr<N>,
+        params: &[&str],
+    ) -> Result<CompiledExpr<N>, InterpretError<'expr>> {
+        let mut compiler = Compiler {
+            interpreter: self,
+            code: Vec::new(),
+            scope: params
+                .iter()
+                .enumerate()
+                .map(|(i, param)| (param.to_string(), Slot::Arg(i)))
+                .collect(),
+            locals: 0,
+            depth: 0,
+            max_depth: 0,
+            calls: Vec::new(),
+        };
+        compiler.compile(expr)?;
+        Ok(CompiledExpr {
+            code: compiler.code,
+            params: params.len(),
+            locals: compiler.locals,
+            depth: compiler.max_depth,
+            strict: self.strict,
+        })
+    }
+}
+
+impl<N: Num> Compiler<'_, N> {
+    fn compile<'expr>(&mut self, expr: &'expr Expr<N>) -> Result<(), InterpretError<'expr>> {
+        match expr {
+            Expr::Eq(..) | Expr::FuncDef(..) => {
+                return Err(InterpretError::NotCompilable("=".into()))
+            }
+            Expr::FuncOrVarMul(id, args, _) => self.compile_call(id, args)?,
+            Expr::If(cond, then, otherwise) => self.compile_if(cond, then, otherwise)?,
+            Expr::Neg(expr, _) => {
+                self.compile(expr)?;
+                self.emit(Instr::Neg);
+            }
+            Expr::Num(n) => self.emit(Instr::Num(n.clone())),
+            // rhs is only evaluated if lhs does not decide the result
+            Expr::Op(OpVal::And, lhs, rhs, _) => {
+                self.compile(lhs)?;
+                let skip = self.jump(Instr::JumpUnless(0));
+                self.compile(rhs)?;
+                self.emit(Instr::Truth);
+                let end = self.jump(Instr::Jump(0));
+                self.patch(skip);
+                self.depth -= 1; // the value of rhs is not there when it is skipped
+                self.emit(Instr::Num(N::zero()));
+                self.patch(end);
+            }
+            Expr::Op(OpVal::Or, lhs, rhs, _) => {
+                self.compile(lhs)?;
+                let skip = self.jump(Instr::JumpUnless(0));
+                self.emit(Instr::Num(N::one()));
+                let end = self.jump(Instr::Jump(0));
+                self.patch(skip);
+                self.depth -= 1; // the 1 is not there when rhs is evaluated
+                self.compile(rhs)?;
+                self.emit(Instr::Truth);
+                self.patch(end);
+            }
+            Expr::Op(op, lhs, rhs, _) => {
+                self.compile(lhs)?;
+                self.compile(rhs)?;
+                self.emit(Instr::Op(*op));
+            }
+            Expr::Var(id, _) => match self.lookup(id) {
+                Some(slot) => self.load(slot),
+                None => match self.interpreter.vars.get(id) {
+                    Some(Variant::Num(n)) => self.emit(Instr::Num(n.clone())),
+                    Some(_) => {
+                        return Err(InterpretError::FunctionNameUsedLikeVar(id.as_str().into()))
+                    }
+                    None => return Err(InterpretError::VarDoesNotExist(id.as_str().into())),
+                },
+            },
+        }
+        Ok(())
+    }
+
+    fn compile_call<'expr>(
+        &mut self,
+        id: &'expr str,
+        args: &'expr [Expr<N>],
+    ) -> Result<(), InterpretError<'expr>> {
+        // a variable followed by parentheses multiplies them
+        let callee = match (self.lookup(id), self.interpreter.vars.get(id)) {
+            (Some(slot), _) => {
+                self.load(slot);
+                return self.compile_var_mul(id, args);
+            }
+            (None, Some(Variant::Num(n))) => {
+                self.emit(Instr::Num(n.clone()));
+                return self.compile_var_mul(id, args);
+            }
+            (None, Some(Variant::Function(func))) => Callee::Function(*func),
+            (None, Some(Variant::AngleFunction(func))) => {
+                Callee::AngleFunction(*func, self.interpreter.angle_mode)
+            }
+            (None, Some(Variant::Closure(func))) => Callee::Closure(func.clone()),
+            (None, Some(Variant::UserFunction(params, body))) => {
+                let (params, body) = (params.clone(), body.clone());
+                return self.inline(id, &params, &body, args);
+            }
+            (None, Some(Variant::LazyFunction(_))) => {
+                return Err(InterpretError::NotCompilable(id.into()))
+            }
+            (None, None) => return Err(InterpretError::VarDoesNotExist(id.into())),
+        };
+        for arg in args {
+            self.compile(arg)?;
+        }
+        self.emit(Instr::Call(callee, args.len(), id.to_string()));
+        Ok(())
+    }
+
+    fn compile_var_mul<'expr>(
+        &mut self,
+        id: &'expr str,
+        args: &'expr [Expr<N>],
+    ) -> Result<(), InterpretError<'expr>> {
+        match args {
+            [arg] => {
+                self.compile(arg)?;
+                self.emit(Instr::Op(OpVal::Mul));
+                Ok(())
+            }
+            _ => Err(InterpretError::VarIsNotFunction(id.into())),
+        }
+    }
+
+    fn compile_if<'expr>(
+        &mut self,
+        cond: &'expr Expr<N>,
+        then: &'expr Expr<N>,
+        otherwise: &'expr Expr<N>,
+    ) -> Result<(), InterpretError<'expr>> {
+        self.compile(cond)?;
+        let skip = self.jump(Instr::JumpUnless(0));
+        self.compile(then)?;
+        let end = self.jump(Instr::Jump(0));
+        self.patch(skip);
+        self.depth -= 1; // only one of the branches leaves its value
+        self.compile(otherwise)?;
+        self.patch(end);
+        Ok(())
+    }
+
+    // The arguments are stored in new locals, which the body sees in place of the params
+    fn inline<'expr>(
+        &mut self,
+        id: &'expr str,
+        params: &[String],
+        body: &Expr<N>,
+        args: &'expr [Expr<N>],
+    ) -> Result<(), InterpretError<'expr>> {
+        ensure_arg_count(params.len(), params.len(), args.len(), id)?;
+        if self.calls.iter().any(|call| call == id) {
+            return Err(InterpretError::NotCompilable(id.into()));
+        }
+        for arg in args {
+            self.compile(arg)?;
+        }
+        let first = self.locals;
+        self.locals += params.len();
+        for slot in (first..self.locals).rev() {
+            self.emit(Instr::Store(slot));
+        }
+        let scope = self.scope.len();
+        for (i, param) in params.iter().enumerate() {
+            self.scope.push((param.clone(), Slot::Local(first + i)));
+        }
+        self.calls.push(id.to_string());
+        // the body belongs to the interpreter, so errors from it must own their ids
+        let result = self.compile(body).map_err(InterpretError::into_owned);
+        self.calls.pop();
+        self.scope.truncate(scope);
+        result
+    }
+
+    fn lookup(&self, id: &str) -> Option<Slot> {
+        self.scope
+            .iter()
+            .rev()
+            .find(|(name, _)| name == id)
+            .map(|(_, slot)| *slot)
+    }
+
+    fn load(&mut self, slot: Slot) {
+        self.emit(match slot {
+            Slot::Arg(i) => Instr::Arg(i),
+            Slot::Local(i) => Instr::Local(i),
+        });
+    }
+
+    // Pushes instr, keeping count of the values it leaves on the stack
+    fn emit(&mut self, instr: Instr<N>) {
+        match &instr {
+            Instr::Num(_) | Instr::Arg(_) | Instr::Local(_) => self.depth += 1,
+            Instr::Store(_) | Instr::Op(_) | Instr::JumpUnless(_) => self.depth -= 1,
+            Instr::Call(_, count, _) => self.depth = self.depth + 1 - count,
+            Instr::Neg | Instr::Truth | Instr::Jump(_) => {}
+        }
+        self.max_depth = self.max_depth.max(self.depth);
+        self.code.push(instr);
+    }
+
+    // Pushes a jump whose target is set by patch
+    fn jump(&mut self, instr: Instr<N>) -> usize {
+        self.emit(instr);
+        self.code.len() - 1
+    }
+
+    fn patch(&mut self, at: usize) {
+        let end = self.code.len();
+        if let Instr::Jump(target) | Instr::JumpUnless(target) = &mut self.code[at] {
+            *target = end;
+        }
+    }
+}
+
+impl<N: Num> CompiledExpr<N> {
+    // Evaluates the expression with args as the values of the params it was compiled with.
+    // Errors are the same as Interpreter::eval returns for the expression.
+    pub fn eval(&self, args: &[N]) -> Result<N, InterpretError<'_>> {
+        self.eval_with(args, &mut Vec::new())
+    }
+
+    // The same as eval, but with the locals and stack in scratch, so evaluating many times
+    // with the same scratch only allocates the first time.
+    pub fn eval_with(&self, args: &[N], scratch: &mut Vec<N>) -> Result<N, InterpretError<'_>> {
+        ensure_arg_count(self.params, self.params, args.len(), "eval")?;
+        // the locals come first, and the stack grows after them
+        let stack = scratch;
+        stack.clear();
+        stack.reserve_exact(self.locals + self.depth);
+        stack.resize(self.locals, N::zero());
+        let mut pc = 0;
+        while let Some(instr) = self.code.get(pc) {
+            pc += 1;
+            match instr {
+                Instr::Num(n) => stack.push(n.clone()),
+                Instr::Arg(i) => stack.push(args[*i].clone()),
+                Instr::Local(i) => stack.push(stack[*i].clone()),
+                Instr::Store(i) => {
+                    let n = pop(stack);
+                    stack[*i] = n;
+                }
+                Instr::Neg => {
+                    let n = pop(stack)
+                        .try_neg()
+                        .map_err(|err| InterpretError::from_num_error(err, "-"))?;
+                    stack.push(n);
+                }
+                Instr::Op(op) => {
+                    let rhs = pop(stack);
+                    let lhs = pop(stack);
+                    stack.push(self.apply(*op, lhs, rhs)?);
+                }
+                Instr::Truth => {
+                    let n = pop(stack);
+                    stack.push(truth(n != N::zero()));
+                }
+                Instr::Call(callee, count, id) => {
+                    let start = stack.len() - count;
+                    let args = &stack[start..];
+                    let result = match callee {
+                        Callee::Function(func) => func(id, args),
+                        Callee::AngleFunction(func, angle_mode) => func(id, args, *angle_mode),
+                        Callee::Closure(func) => func(id, args),
+                    }?;
+                    stack.truncate(start);
+                    stack.push(self.check_result(result, id)?);
+                }
+                Instr::Jump(target) => pc = *target,
+                Instr::JumpUnless(target) => {
+                    if pop(stack) == N::zero() {
+                        pc = *target;
+                    }
+                }
+            }
+        }
+        Ok(pop(stack))
+    }
+
+    // The same as the interpreter does for Expr::Op
+    fn apply(&self, op: OpVal, lhs: N, rhs: N) -> Result<N, InterpretError<'static>> {
+        if (op == OpVal::Div || op == OpVal::Mod || op == OpVal::In) && rhs == N::zero() {
+            return Err(InterpretError::DivisionByZero);
+        }
+        let (result, symbol) = match op {
+            OpVal::Add => (lhs.try_add(rhs), "+"),
+            OpVal::Sub => (lhs.try_sub(rhs), "-"),
+            OpVal::Mul => (lhs.try_mul(rhs), "*"),
+            OpVal::Div => (lhs.try_div(rhs), "/"),
+            OpVal::Mod => (lhs.try_rem(rhs), "%"),
+            OpVal::Pow => (lhs.try_pow(rhs), "^"),
+            OpVal::In => (lhs.try_convert(rhs), "in"),
+            OpVal::Less => (Ok(truth(lhs < rhs)), "<"),
+            OpVal::LessEq => (Ok(truth(lhs <= rhs)), "<="),
+            OpVal::Greater => (Ok(truth(lhs > rhs)), ">"),
+            OpVal::GreaterEq => (Ok(truth(lhs >= rhs)), ">="),
+            OpVal::Equal => (Ok(truth(lhs == rhs)), "=="),
+            OpVal::NotEqual => (Ok(truth(lhs != rhs)), "!="),
+            _ => unreachable!(),
+        };
+        result
+            .map_err(|err| InterpretError::from_num_error(err, symbol))
+            .and_then(|result| self.check_result(result, symbol))
+    }
+
+    // In strict mode, NaN and infinite results are errors instead of values
+    fn check_result<'a>(&self, result: N, name: &'a str) -> Result<N, InterpretError<'a>> {
+        if self.strict && result.is_nan() {
+            Err(InterpretError::DomainError(name.into()))
+        } else if self.strict && result.is_infinite() {
+            Err(InterpretError::Overflow(name.into()))
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+// Programs are compiled so every operation has its operands on the stack
+fn pop<N>(stack: &mut Vec<N>) -> N {
+    stack
+        .pop()
+        .expect("compiled programs keep the stack balanced")
+}
@@ -0,0 +1,397 @@
+use crate::interpreter::MAX_FACTORIAL;
+use crate::{
+    ensure_arg_count, register_lazy_builtins, unary_fn, InterpretError, Interpreter, Num, NumError,
+    Variant,
+};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub const I: Complex = Complex { re: 0.0, im: 1.0 };
+
+    #[inline(always)]
+    pub fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    pub fn from_polar(r: f64, theta: f64) -> Complex {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+
+    #[inline(always)]
+    pub fn is_real(&self) -> bool {
+        self.im == 0.0
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+
+    // Angle from the positive real axis in radians, in (-pi, pi]
+    pub fn arg(self) -> f64 {
+        // -0 imaginary parts come from negating reals, they must not flip the sign
+        if self.is_real() && self.re < 0.0 {
+            return std::f64::consts::PI;
+        }
+        self.im.atan2(self.re)
+    }
+
+    pub fn conj(self) -> Complex {
+        Complex::new(self.re, -self.im)
+    }
+
+    fn scale(self, factor: f64) -> Complex {
+        Complex::new(self.re * factor, self.im * factor)
+    }
+
+    // Principal square root, exact for negative reals so sqrt(-4) is 2i
+    pub fn sqrt(self) -> Complex {
+        if self.is_real() && self.re >= 0.0 {
+            return Complex::new(self.re.sqrt(), 0.0);
+        }
+        let r = self.abs();
+        let re = ((r + self.re) / 2.0).sqrt();
+        let im = ((r - self.re) / 2.0).sqrt();
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+
+    pub fn exp(self) -> Complex {
+        Complex::from_polar(self.re.exp(), self.im)
+    }
+
+    // Principal natural logarithm
+    pub fn ln(self) -> Complex {
+        Complex::new(self.abs().ln(), self.arg())
+    }
+
+    pub fn sin(self) -> Complex {
+        Complex::new(
+            self.re.sin() * self.im.cosh(),
+            self.re.cos() * self.im.sinh(),
+        )
+    }
+
+    pub fn cos(self) -> Complex {
+        Complex::new(
+            self.re.cos() * self.im.cosh(),
+            -self.re.sin() * self.im.sinh(),
+        )
+    }
+
+    pub fn sinh(self) -> Complex {
+        Complex::new(
+            self.re.sinh() * self.im.cos(),
+            self.re.cosh() * self.im.sin(),
+        )
+    }
+
+    pub fn cosh(self) -> Complex {
+        Complex::new(
+            self.re.cosh() * self.im.cos(),
+            self.re.sinh() * self.im.sin(),
+        )
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Complex {
+        Complex::new(re, 0.0)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+
+    fn div(self, other: Complex) -> Complex {
+        if other.is_real() {
+            return Complex::new(self.re / other.re, self.im / other.re);
+        }
+        let denom = other.re * other.re + other.im * other.im;
+        Complex::new(
+            (self.re * other.re + self.im * other.im) / denom,
+            (self.im * other.re - self.re * other.im) / denom,
+        )
+    }
+}
+
+// Only defined between real numbers, anything else is NaN
+impl Rem for Complex {
+    type Output = Complex;
+
+    fn rem(self, other: Complex) -> Complex {
+        if self.is_real() && other.is_real() {
+            Complex::from(self.re % other.re)
+        } else {
+            Complex::new(f64::NAN, f64::NAN)
+        }
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+
+    fn neg(self) -> Complex {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+impl AddAssign for Complex {
+    fn add_assign(&mut self, other: Complex) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Complex {
+    fn sub_assign(&mut self, other: Complex) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for Complex {
+    fn mul_assign(&mut self, other: Complex) {
+        *self = *self * other;
+    }
+}
+
+impl DivAssign for Complex {
+    fn div_assign(&mut self, other: Complex) {
+        *self = *self / other;
+    }
+}
+
+// Complex numbers are only ordered along the same horizontal line, which includes the real numbers
+impl PartialOrd for Complex {
+    fn partial_cmp(&self, other: &Complex) -> Option<Ordering> {
+        if self.im == other.im {
+            self.re.partial_cmp(&other.re)
+        } else {
+            None
+        }
+    }
+}
+
+// Parses real numbers like 2.5 and imaginary numbers like 4i or 4j
+impl FromStr for Complex {
+    type Err = std::num::ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_suffix(['i', 'j']) {
+            Some("") => Ok(Complex::I),
+            Some(im) => Ok(Complex::new(0.0, im.parse()?)),
+            None => Ok(Complex::from(s.parse::<f64>()?)),
+        }
+    }
+}
+
+impl fmt::Display for Complex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_real() {
+            write!(f, "{}", self.re)
+        } else if self.re == 0.0 {
+            write!(f, "{}i", self.im)
+        } else if self.im < 0.0 {
+            write!(f, "{}-{}i", self.re, -self.im)
+        } else {
+            write!(f, "{}+{}i", self.re, self.im)
+        }
+    }
+}
+
+impl Num for Complex {
+    #[inline(always)]
+    fn zero() -> Self {
+        Complex::new(0.0, 0.0)
+    }
+    #[inline(always)]
+    fn one() -> Self {
+        Complex::new(1.0, 0.0)
+    }
+    #[inline(always)]
+    fn is_whole(&self) -> bool {
+        self.is_real() && self.re.fract() == 0.0
+    }
+    fn pow(self, other: Self) -> Self {
+        if self.is_real() && other.is_real() && (self.re >= 0.0 || other.re.fract() == 0.0) {
+            Complex::from(self.re.powf(other.re))
+        } else if other == Complex::from(0.5) {
+            self.sqrt()
+        } else if self == Complex::zero() {
+            if other.re > 0.0 {
+                Complex::zero()
+            } else {
+                Complex::new(f64::NAN, f64::NAN)
+            }
+        } else {
+            (other * self.ln()).exp()
+        }
+    }
+    #[inline(always)]
+    fn is_nan(&self) -> bool {
+        self.re.is_nan() || self.im.is_nan()
+    }
+    #[inline(always)]
+    fn is_infinite(&self) -> bool {
+        self.re.is_infinite() || self.im.is_infinite()
+    }
+
+    fn try_neg(self) -> Result<Self, NumError> {
+        Ok(-self)
+    }
+    fn try_rem(self, other: Self) -> Result<Self, NumError> {
+        if self.is_real() && other.is_real() {
+            Ok(self % other)
+        } else {
+            Err(NumError::Domain)
+        }
+    }
+}
+
+// Registers a trigonometric function, converting its argument from the angle mode to radians
+macro_rules! complex_trig_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::AngleFunction(|id, args, mode| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                Ok($func(args[0].scale(mode.to_radians(1.0))))
+            }),
+        )
+    };
+}
+
+impl Default for Interpreter<Complex> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        let vars = &mut interpreter.vars;
+        vars.insert(
+            String::from("pi"),
+            Variant::Num(Complex::from(std::f64::consts::PI)),
+        );
+        vars.insert(
+            String::from("e"),
+            Variant::Num(Complex::from(std::f64::consts::E)),
+        );
+        vars.insert(
+            String::from("tau"),
+            Variant::Num(Complex::from(std::f64::consts::TAU)),
+        );
+        vars.insert(
+            String::from("phi"),
+            Variant::Num(Complex::from(1.618_033_988_749_895_f64)),
+        );
+        vars.insert(String::from("i"), Variant::Num(Complex::I));
+        vars.insert(String::from("j"), Variant::Num(Complex::I)); // the electrical engineering spelling
+
+        // parts
+        unary_fn!(vars, "re", |z: Complex| Complex::from(z.re));
+        unary_fn!(vars, "im", |z: Complex| Complex::from(z.im));
+        unary_fn!(vars, "conj", Complex::conj);
+        unary_fn!(vars, "abs", |z: Complex| Complex::from(z.abs()));
+        vars.insert(
+            String::from("arg"),
+            Variant::AngleFunction(|id, args, mode| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                Ok(Complex::from(mode.from_radians(args[0].arg())))
+            }),
+        );
+        vars.insert(
+            String::from("polar"),
+            Variant::AngleFunction(|id, args, mode| {
+                // polar(r, theta) is the phasor with magnitude r and angle theta
+                ensure_arg_count(2, 2, args.len(), id)?;
+                let (r, theta) = (args[0], args[1]);
+                if !r.is_real() || !theta.is_real() {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                Ok(Complex::from_polar(r.re, mode.to_radians(theta.re)))
+            }),
+        );
+
+        // roots, exponentials and logarithms
+        unary_fn!(vars, "sqrt", Complex::sqrt);
+        unary_fn!(vars, "exp", Complex::exp);
+        unary_fn!(vars, "ln", Complex::ln, |z| z != Complex::zero());
+        vars.insert(
+            String::from("log"),
+            Variant::Function(|id, args| {
+                // log(x) is base 10, log(x, base) is any other base
+                ensure_arg_count(1, 2, args.len(), id)?;
+                let base = args.get(1).copied().unwrap_or(Complex::from(10.0));
+                if args[0] == Complex::zero() || base == Complex::zero() || base == Complex::one() {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                Ok(args[0].ln() / base.ln())
+            }),
+        );
+
+        // trigonometry
+        complex_trig_fn!(vars, "sin", Complex::sin);
+        complex_trig_fn!(vars, "cos", Complex::cos);
+        complex_trig_fn!(vars, "tan", |z: Complex| z.sin() / z.cos());
+        vars.insert(
+            String::from("deg"),
+            Variant::AngleFunction(|id, args, mode| {
+                // converts degrees to the angle mode, this is what 90° and 90deg mean
+                ensure_arg_count(1, 1, args.len(), id)?;
+                Ok(args[0].scale(mode.from_radians(1f64.to_radians())))
+            }),
+        );
+        unary_fn!(vars, "sinh", Complex::sinh);
+        unary_fn!(vars, "cosh", Complex::cosh);
+        unary_fn!(vars, "tanh", |z: Complex| z.sinh() / z.cosh());
+
+        register_lazy_builtins(vars);
+        vars.insert(
+            String::from("factorial"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let n = args[0];
+                if !n.is_whole() || n.re < 0.0 {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                if n.re > MAX_FACTORIAL {
+                    return Err(InterpretError::Overflow(id.into()));
+                }
+                Ok(Complex::from(
+                    (2..=n.re as u64).fold(1.0, |acc, i| acc * i as f64),
+                ))
+            }),
+        );
+        interpreter
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{
+    ensure_arg_count, register_lazy_builtins, unary_fn, AngleMode, InterpretError, Interpreter,
+    Num, NumError, Variant,
+};
+use rust_decimal::{Decimal, MathematicalOps, RoundingStrategy};
+
+impl Num for Decimal {
+    #[inline(always)]
+    fn zero() -> Self {
+        Decimal::ZERO
+    }
+    #[inline(always)]
+    fn one() -> Self {
+        Decimal::ONE
+    }
+    #[inline(always)]
+    fn is_whole(&self) -> bool {
+        self.fract().is_zero()
+    }
+    #[inline(always)]
+    fn pow(self, other: Self) -> Self {
+        self.powd(other)
+    }
+
+    // Decimal operators panic on overflow, so the interpreter goes through these
+    fn try_neg(self) -> Result<Self, NumError> {
+        Ok(-self)
+    }
+    fn try_add(self, other: Self) -> Result<Self, NumError> {
+        self.checked_add(other).ok_or(NumError::Overflow)
+    }
+    fn try_sub(self, other: Self) -> Result<Self, NumError> {
+        self.checked_sub(other).ok_or(NumError::Overflow)
+    }
+    fn try_mul(self, other: Self) -> Result<Self, NumError> {
+        self.checked_mul(other).ok_or(NumError::Overflow)
+    }
+    fn try_div(self, other: Self) -> Result<Self, NumError> {
+        self.checked_div(other).ok_or(NumError::Overflow)
+    }
+    fn try_rem(self, other: Self) -> Result<Self, NumError> {
+        self.checked_rem(other).ok_or(NumError::Overflow)
+    }
+    fn try_pow(self, other: Self) -> Result<Self, NumError> {
+        if self.is_sign_negative() && !other.is_whole() {
+            return Err(NumError::Domain);
+        }
+        // powd approximates square roots less precisely than sqrt
+        if other == Decimal::new(5, 1) {
+            return self
+                .sqrt()
+                .map(|root| root.normalize())
+                .ok_or(NumError::Domain);
+        }
+        self.checked_powd(other).ok_or(NumError::Overflow)
+    }
+}
+
+// Both conversions are None if the angle is too large to convert
+fn to_radians(mode: AngleMode, angle: Decimal) -> Option<Decimal> {
+    match mode {
+        AngleMode::Radians => Some(angle),
+        AngleMode::Degrees => angle
+            .checked_mul(Decimal::PI)?
+            .checked_div(Decimal::from(180)),
+        AngleMode::Gradians => angle
+            .checked_mul(Decimal::PI)?
+            .checked_div(Decimal::from(200)),
+    }
+}
+
+fn from_radians(mode: AngleMode, angle: Decimal) -> Option<Decimal> {
+    match mode {
+        AngleMode::Radians => Some(angle),
+        AngleMode::Degrees => angle
+            .checked_mul(Decimal::from(180))?
+            .checked_div(Decimal::PI),
+        AngleMode::Gradians => angle
+            .checked_mul(Decimal::from(200))?
+            .checked_div(Decimal::PI),
+    }
+}
+
+// Registers a trigonometric function, converting its argument from the angle mode to radians
+macro_rules! decimal_trig_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::AngleFunction(|id, args, mode| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let angle = to_radians(mode, args[0]).ok_or(InterpretError::Overflow(id.into()))?;
+                $func(&angle).ok_or(InterpretError::DomainError(id.into()))
+            }),
+        )
+    };
+}
+
+impl Default for Interpreter<Decimal> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        let vars = &mut interpreter.vars;
+        vars.insert(String::from("pi"), Variant::Num(Decimal::PI));
+        vars.insert(String::from("e"), Variant::Num(Decimal::E));
+        vars.insert(String::from("tau"), Variant::Num(Decimal::TWO_PI));
+        vars.insert(
+            String::from("phi"),
+            Variant::Num(Decimal::from_i128_with_scale(
+                16_180_339_887_498_948_482,
+                19,
+            )),
+        );
+        vars.insert(
+            String::from("lightspeed"),
+            Variant::Num(Decimal::from(299792458)),
+        ); // the speed of light in vacuum in m/s
+        vars.insert(
+            String::from("gravconst"),
+            Variant::Num(Decimal::new(667430, 16)),
+        ); // gravitational constant in m^3/kg/s^2
+
+        // roots and rounding
+        unary_fn!(vars, "abs", |x: Decimal| x.abs());
+        unary_fn!(
+            vars,
+            "sqrt",
+            |x: Decimal| x.sqrt().unwrap_or_default(),
+            |x| x >= Decimal::ZERO
+        );
+        unary_fn!(vars, "floor", |x: Decimal| x.floor());
+        unary_fn!(vars, "ceil", |x: Decimal| x.ceil());
+        unary_fn!(vars, "round", |x: Decimal| x
+            .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero));
+        unary_fn!(vars, "trunc", |x: Decimal| x.trunc());
+        unary_fn!(vars, "sign", |x: Decimal| if x.is_zero() {
+            x
+        } else if x.is_sign_negative() {
+            -Decimal::ONE
+        } else {
+            Decimal::ONE
+        });
+
+        // trigonometry
+        decimal_trig_fn!(vars, "sin", Decimal::checked_sin);
+        decimal_trig_fn!(vars, "cos", Decimal::checked_cos);
+        decimal_trig_fn!(vars, "tan", Decimal::checked_tan);
+        vars.insert(
+            String::from("deg"),
+            Variant::AngleFunction(|id, args, mode| {
+                // converts degrees to the angle mode, this is what 90° and 90deg mean
+                ensure_arg_count(1, 1, args.len(), id)?;
+                to_radians(AngleMode::Degrees, args[0])
+                    .and_then(|angle| from_radians(mode, angle))
+                    .ok_or(InterpretError::Overflow(id.into()))
+            }),
+        );
+
+        // exponentials and logarithms
+        vars.insert(
+            String::from("exp"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                args[0]
+                    .checked_exp()
+                    .ok_or(InterpretError::Overflow(id.into()))
+            }),
+        );
+        unary_fn!(vars, "ln", |x: Decimal| x.ln(), |x| x > Decimal::ZERO);
+        unary_fn!(vars, "log10", |x: Decimal| x.log10(), |x| x > Decimal::ZERO);
+        vars.insert(
+            String::from("log"),
+            Variant::Function(|id, args| {
+                // log(x) is base 10, log(x, base) is any other base
+                ensure_arg_count(1, 2, args.len(), id)?;
+                let x = args[0];
+                match args.get(1) {
+                    None if x > Decimal::ZERO => Ok(x.log10()),
+                    Some(base)
+                        if x > Decimal::ZERO && *base > Decimal::ZERO && *base != Decimal::ONE =>
+                    {
+                        // bases this close to 1 have a logarithm of 0 at this precision
+                        let base = base.ln();
+                        if base.is_zero() {
+                            return Err(InterpretError::DomainError(id.into()));
+                        }
+                        x.ln()
+                            .checked_div(base)
+                            .ok_or(InterpretError::Overflow(id.into()))
+                    }
+                    _ => Err(InterpretError::DomainError(id.into())),
+                }
+            }),
+        );
+
+        // variadic
+        vars.insert(
+            String::from("min"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, usize::MAX, args.len(), id)?;
+                Ok(args.iter().copied().fold(Decimal::MAX, Decimal::min))
+            }),
+        );
+        vars.insert(
+            String::from("max"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, usize::MAX, args.len(), id)?;
+                Ok(args.iter().copied().fold(Decimal::MIN, Decimal::max))
+            }),
+        );
+        register_lazy_builtins(vars);
+
+        vars.insert(
+            String::from("factorial"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let n = args[0];
+                if n.is_sign_negative() || !n.is_whole() {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                let mut result = Decimal::ONE;
+                let mut i = Decimal::TWO;
+                while i <= n {
+                    result = result
+                        .checked_mul(i)
+                        .ok_or(InterpretError::Overflow(id.into()))?;
+                    i += Decimal::ONE;
+                }
+                Ok(result)
+            }),
+        );
+        interpreter
+    }
+}
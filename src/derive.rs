@@ -0,0 +1,233 @@
+use crate::solver::contains;
+use crate::{Expr, InterpretError, Num, OpVal};
+use std::ops::Range;
+
+// Returns the derivative of expr with respect to x. Builtins are assumed to be the default ones,
+// with angles in radians. New nodes take the span of the node they came from,
+// so errors evaluating the derivative point at the original call or operator.
+pub fn derive<'expr, N: Num>(
+    expr: &'expr Expr<N>,
+    x: &str,
+) -> Result<Expr<N>, InterpretError<'expr>> {
+    match expr {
+        Expr::Eq(..) | Expr::FuncDef(..) => Err(InterpretError::NotDifferentiable("=".into())),
+        _ if !contains(expr, x) => Ok(Expr::Num(N::zero())),
+        Expr::FuncOrVarMul(id, args, span) => derive_call(id, args, span, x),
+        // a piecewise expression has a piecewise derivative
+        Expr::If(cond, then, otherwise) => Ok(Expr::If(
+            cond.clone(),
+            Box::new(derive(then, x)?),
+            Box::new(derive(otherwise, x)?),
+        )),
+        Expr::Neg(u, span) => Ok(neg(derive(u, x)?, span)),
+        Expr::Num(_) => Ok(Expr::Num(N::zero())),
+        Expr::Op(op, u, v, span) => derive_op(*op, u, v, span, x),
+        Expr::Var(..) => Ok(Expr::Num(N::one())),
+    }
+}
+
+fn derive_op<'expr, N: Num>(
+    op: OpVal,
+    u: &'expr Expr<N>,
+    v: &'expr Expr<N>,
+    span: &Range<usize>,
+    x: &str,
+) -> Result<Expr<N>, InterpretError<'expr>> {
+    let (du, dv) = (derive(u, x)?, derive(v, x)?);
+    let (u, v) = (u.clone(), v.clone());
+    Ok(match op {
+        OpVal::Add => add(du, dv, span),
+        OpVal::Sub => sub(du, dv, span),
+        OpVal::Mul => add(mul(du, v, span), mul(u, dv, span), span),
+        OpVal::Div if !contains(&v, x) => div(du, v, span),
+        OpVal::Div => div(
+            sub(mul(du, v.clone(), span), mul(u, dv, span), span),
+            pow(v, num(2), span),
+            span,
+        ),
+        // (u^c)' = c * u^(c - 1) * u'
+        OpVal::Pow if !contains(&v, x) => {
+            let exponent = sub(v.clone(), num(1), span);
+            mul(mul(v, pow(u, exponent, span), span), du, span)
+        }
+        // (c^v)' = c^v * ln(c) * v'
+        OpVal::Pow if !contains(&u, x) => {
+            let ln = call("ln", u.clone(), span);
+            mul(mul(pow(u, v, span), ln, span), dv, span)
+        }
+        // (u^v)' = u^v * (v' * ln(u) + v * u' / u)
+        OpVal::Pow => {
+            let ln = call("ln", u.clone(), span);
+            let inner = add(
+                mul(dv, ln, span),
+                div(mul(v.clone(), du, span), u.clone(), span),
+                span,
+            );
+            mul(pow(u, v, span), inner, span)
+        }
+        // u % c and u in c only differ from u by a constant step or factor
+        OpVal::Mod if !contains(&v, x) => du,
+        OpVal::In if !contains(&v, x) => op_expr(OpVal::In, du, v, span),
+        // comparisons and logic are constant wherever their result does not jump
+        OpVal::Less
+        | OpVal::LessEq
+        | OpVal::Greater
+        | OpVal::GreaterEq
+        | OpVal::Equal
+        | OpVal::NotEqual
+        | OpVal::And
+        | OpVal::Or => Expr::Num(N::zero()),
+        OpVal::Mod => return Err(InterpretError::NotDifferentiable("%".into())),
+        _ => return Err(InterpretError::NotDifferentiable("in".into())),
+    })
+}
+
+fn derive_call<'expr, N: Num>(
+    id: &'expr str,
+    args: &'expr [Expr<N>],
+    span: &Range<usize>,
+    x: &str,
+) -> Result<Expr<N>, InterpretError<'expr>> {
+    // x(u) is x * u
+    if id == x && args.len() == 1 {
+        let u = &args[0];
+        let var = Expr::Var(x.to_string(), span.clone());
+        return Ok(add(u.clone(), mul(var, derive(u, x)?, span), span));
+    }
+    // log(u, c) is ln(u) / ln(c)
+    if let [u, base] = args {
+        if id == "log" && !contains(base, x) {
+            let denominator = mul(u.clone(), call("ln", base.clone(), span), span);
+            return Ok(div(derive(u, x)?, denominator, span));
+        }
+    }
+    let u = match args {
+        [u] => u,
+        _ => return Err(InterpretError::NotDifferentiable(id.into())),
+    };
+    let du = derive(u, x)?;
+    let u = u.clone();
+    let squared = |u: Expr<N>| pow(u, num(2), span);
+    Ok(match id {
+        "sqrt" => div(du, mul(num(2), call("sqrt", u, span), span), span),
+        "cbrt" => div(du, mul(num(3), squared(call("cbrt", u, span)), span), span),
+        "abs" => mul(call("sign", u, span), du, span),
+        "exp" => mul(call("exp", u, span), du, span),
+        "ln" => div(du, u, span),
+        "log" | "log10" => div(du, mul(u, call("ln", num(10), span), span), span),
+        "log2" => div(du, mul(u, call("ln", num(2), span), span), span),
+        "sin" => mul(call("cos", u, span), du, span),
+        "cos" => neg(mul(call("sin", u, span), du, span), span),
+        "tan" => div(du, squared(call("cos", u, span)), span),
+        "sec" => div(
+            mul(call("sin", u.clone(), span), du, span),
+            squared(call("cos", u, span)),
+            span,
+        ),
+        "csc" => neg(
+            div(
+                mul(call("cos", u.clone(), span), du, span),
+                squared(call("sin", u, span)),
+                span,
+            ),
+            span,
+        ),
+        "cot" => neg(div(du, squared(call("sin", u, span)), span), span),
+        "asin" => div(du, call("sqrt", sub(num(1), squared(u), span), span), span),
+        "acos" => neg(
+            div(du, call("sqrt", sub(num(1), squared(u), span), span), span),
+            span,
+        ),
+        "atan" => div(du, add(num(1), squared(u), span), span),
+        "sinh" => mul(call("cosh", u, span), du, span),
+        "cosh" => mul(call("sinh", u, span), du, span),
+        "tanh" => div(du, squared(call("cosh", u, span)), span),
+        "asinh" => div(du, call("sqrt", add(squared(u), num(1), span), span), span),
+        "acosh" => div(du, call("sqrt", sub(squared(u), num(1), span), span), span),
+        "atanh" => div(du, sub(num(1), squared(u), span), span),
+        // steps are flat everywhere except where they jump
+        "sign" | "floor" | "ceil" | "round" | "trunc" => Expr::Num(N::zero()),
+        _ => return Err(InterpretError::NotDifferentiable(id.into())),
+    })
+}
+
+fn num<N: Num>(n: u8) -> Expr<N> {
+    Expr::Num((0..n).fold(N::zero(), |acc, _| acc + N::one()))
+}
+
+fn is<N: Num>(expr: &Expr<N>, n: N) -> bool {
+    matches!(expr, Expr::Num(m) if *m == n)
+}
+
+fn call<N: Num>(id: &str, arg: Expr<N>, span: &Range<usize>) -> Expr<N> {
+    Expr::FuncOrVarMul(id.to_string(), vec![arg], span.clone())
+}
+
+fn op_expr<N: Num>(op: OpVal, lhs: Expr<N>, rhs: Expr<N>, span: &Range<usize>) -> Expr<N> {
+    Expr::Op(op, Box::new(lhs), Box::new(rhs), span.clone())
+}
+
+// The constructors below leave out adding 0 and multiplying by 1, which the rules produce a lot of
+
+fn neg<N: Num>(expr: Expr<N>, span: &Range<usize>) -> Expr<N> {
+    match expr {
+        Expr::Num(n) if n == N::zero() => Expr::Num(n),
+        Expr::Neg(expr, _) => *expr,
+        expr => Expr::Neg(Box::new(expr), span.clone()),
+    }
+}
+
+fn add<N: Num>(lhs: Expr<N>, rhs: Expr<N>, span: &Range<usize>) -> Expr<N> {
+    if is(&lhs, N::zero()) {
+        rhs
+    } else if is(&rhs, N::zero()) {
+        lhs
+    } else {
+        op_expr(OpVal::Add, lhs, rhs, span)
+    }
+}
+
+fn sub<N: Num>(lhs: Expr<N>, rhs: Expr<N>, span: &Range<usize>) -> Expr<N> {
+    match (lhs, rhs) {
+        (lhs, rhs) if is(&rhs, N::zero()) => lhs,
+        (lhs, rhs) if is(&lhs, N::zero()) => neg(rhs, span),
+        // keeps exponents such as 2 - 1 readable
+        (Expr::Num(a), Expr::Num(b)) => match a.clone().try_sub(b.clone()) {
+            Ok(n) => Expr::Num(n),
+            Err(_) => op_expr(OpVal::Sub, Expr::Num(a), Expr::Num(b), span),
+        },
+        (lhs, rhs) => op_expr(OpVal::Sub, lhs, rhs, span),
+    }
+}
+
+fn mul<N: Num>(lhs: Expr<N>, rhs: Expr<N>, span: &Range<usize>) -> Expr<N> {
+    if is(&lhs, N::zero()) || is(&rhs, N::zero()) {
+        Expr::Num(N::zero())
+    } else if is(&lhs, N::one()) {
+        rhs
+    } else if is(&rhs, N::one()) {
+        lhs
+    } else {
+        op_expr(OpVal::Mul, lhs, rhs, span)
+    }
+}
+
+fn div<N: Num>(lhs: Expr<N>, rhs: Expr<N>, span: &Range<usize>) -> Expr<N> {
+    if is(&lhs, N::zero()) {
+        Expr::Num(N::zero())
+    } else if is(&rhs, N::one()) {
+        lhs
+    } else {
+        op_expr(OpVal::Div, lhs, rhs, span)
+    }
+}
+
+fn pow<N: Num>(lhs: Expr<N>, rhs: Expr<N>, span: &Range<usize>) -> Expr<N> {
+    if is(&rhs, N::zero()) {
+        Expr::Num(N::one())
+    } else if is(&rhs, N::one()) {
+        lhs
+    } else {
+        op_expr(OpVal::Pow, lhs, rhs, span)
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{InterpretError, Num, ParseError, ParseErrorCode, TokenizeError, TokenizeErrorCode};
+use std::fmt;
+use std::ops::Range;
+
+// Why tokenizing, parsing or interpreting failed. Unlike the errors of each stage,
+// it owns its data, so it can be returned from eval_str and boxed as a std::error::Error.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    InvalidNumber(String),
+    UnrecognizedChar(char),
+    NoUnits(String), // `in` or `to`
+    ExpectedValue,
+    ExpectedClosingParen,
+    ExpectedColon,
+    ExpectedArgs(String, usize), // Name of the function and how many it takes
+    UnexpectedToken(String),     // Text of the token
+    UnexpectedEOF,
+    Interpret(InterpretError<'static>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub span: Range<usize>, // Bytes of the input that caused the error
+}
+
+impl Error {
+    // The parser only borrows its tokens, so the input is needed to describe unexpected ones
+    pub fn from_parse<N: Num>(err: ParseError<'_, N>, input: &str) -> Error {
+        let kind = match err.code {
+            ParseErrorCode::ExpectedValue => ErrorKind::ExpectedValue,
+            ParseErrorCode::ExpectedClosingParen => ErrorKind::ExpectedClosingParen,
+            ParseErrorCode::ExpectedColon => ErrorKind::ExpectedColon,
+            ParseErrorCode::ExpectedArgs(id, n) => ErrorKind::ExpectedArgs(id.to_string(), n),
+            ParseErrorCode::UnexpectedToken(tok) => {
+                ErrorKind::UnexpectedToken(input[tok.span.clone()].to_string())
+            }
+            ParseErrorCode::UnexpectedEOF => {
+                // point just past the end, where something else was expected
+                return Error {
+                    kind: ErrorKind::UnexpectedEOF,
+                    span: input.len()..input.len() + 1,
+                };
+            }
+        };
+        Error {
+            kind,
+            span: err.span,
+        }
+    }
+}
+
+impl From<TokenizeError<'_>> for Error {
+    fn from(err: TokenizeError<'_>) -> Error {
+        let kind = match err.code {
+            TokenizeErrorCode::InvalidNumber(text) => ErrorKind::InvalidNumber(text.to_string()),
+            TokenizeErrorCode::UnrecognizedChar(c) => ErrorKind::UnrecognizedChar(c),
+            TokenizeErrorCode::NoUnits(word) => ErrorKind::NoUnits(word.to_string()),
+        };
+        Error {
+            kind,
+            span: err.span,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::InvalidNumber(text) => write!(f, "{:?} is not a valid number.", text),
+            ErrorKind::UnrecognizedChar(c) => write!(f, "Unrecognized character {:?}.", c),
+            ErrorKind::NoUnits(word) => {
+                write!(
+                    f,
+                    "The number type has no units to convert with {:?}.",
+                    word
+                )
+            }
+            ErrorKind::ExpectedValue => write!(f, "Expected a value."),
+            ErrorKind::ExpectedClosingParen => write!(f, "Expected a closing parenthesis."),
+            ErrorKind::ExpectedColon => write!(f, "Expected \":\" after the \"?\" branch."),
+            ErrorKind::ExpectedArgs(id, n) => {
+                write!(
+                    f,
+                    "Function {:?} takes exactly {} argument{}.",
+                    id,
+                    n,
+                    s_if(*n != 1)
+                )
+            }
+            ErrorKind::UnexpectedToken(text) => write!(f, "Unexpected {:?}.", text),
+            ErrorKind::UnexpectedEOF => write!(f, "Unexpected end of input."),
+            ErrorKind::Interpret(err) => err.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (at {}..{})",
+            self.kind, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[inline(always)]
+fn s_if(b: bool) -> &'static str {
+    if b {
+        "s"
+    } else {
+        ""
+    }
+}
+
+impl fmt::Display for InterpretError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InterpretError::TooFewArgs(id, n) => write!(
+                f,
+                "Function {:?} did not receive minimum of {} argument{}.",
+                id,
+                n,
+                s_if(*n != 1)
+            ),
+            InterpretError::TooManyArgs(id, n) => write!(
+                f,
+                "Function {:?} received more than the maximum {} argument{}.",
+                id,
+                n,
+                s_if(*n != 1)
+            ),
+            InterpretError::VarDoesNotExist(id) => {
+                write!(f, "No variable or function {:?} exists.", id)
+            }
+            InterpretError::VarIsNotFunction(id) => write!(
+                f,
+                "The variable {:?} cannot be used like a function with arguments.",
+                id
+            ),
+            InterpretError::FunctionNameUsedLikeVar(id) => {
+                write!(f, "The function {:?} cannot be used without arguments.", id)
+            }
+            InterpretError::NoUnknown => {
+                write!(f, "The equation has no unknown variable to solve for.")
+            }
+            InterpretError::MultipleUnknowns(id1, id2) => write!(
+                f,
+                "The equation has more than one unknown variable ({:?} and {:?}).",
+                id1, id2
+            ),
+            InterpretError::NoSolution(id) => write!(f, "No solution exists for {:?}.", id),
+            InterpretError::InfiniteSolutions(id) => {
+                write!(f, "Every value of {:?} is a solution.", id)
+            }
+            InterpretError::RecursionLimit(id) => {
+                write!(f, "The function {:?} recursed too deeply.", id)
+            }
+            InterpretError::DivisionByZero => write!(f, "Division by zero."),
+            InterpretError::DomainError(id) => {
+                write!(f, "Input is outside of the domain of {:?}.", id)
+            }
+            InterpretError::Overflow(id) => {
+                write!(f, "The result of {:?} does not fit in the number type.", id)
+            }
+            InterpretError::DimensionMismatch(id) => {
+                write!(f, "The units given to {:?} do not match.", id)
+            }
+            InterpretError::NotDifferentiable(id) => {
+                write!(f, "The derivative of {:?} is not known.", id)
+            }
+            InterpretError::NotCompilable(id) => {
+                write!(f, "{:?} cannot be compiled.", id)
+            }
+        }
+    }
+}
@@ -1,11 +1,150 @@
 use crate::{Num, OpVal};
+use std::fmt;
+use std::ops::Range;
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum Expr<'input, N: Num> {
-    Eq(Box<Expr<'input, N>>, Box<Expr<'input, N>>),
-    FuncOrVarMul(&'input str, Vec<Expr<'input, N>>),
-    Neg(Box<Expr<'input, N>>),
-    Num(&'input N),
-    Op(OpVal, Box<Expr<'input, N>>, Box<Expr<'input, N>>),
-    Var(&'input str),
+// Expressions own their identifiers and numbers, so they can outlive the input and tokens.
+// Spans are the byte ranges in the input that interpret errors point at.
+#[derive(Debug, Clone)]
+pub enum Expr<N: Num> {
+    Eq(Box<Expr<N>>, Box<Expr<N>>, Range<usize>), // Lhs, rhs, span of '='
+    FuncDef(String, Vec<String>, Box<Expr<N>>, Range<usize>), // Id, params, body, span of '='
+    FuncOrVarMul(String, Vec<Expr<N>>, Range<usize>), // Id, args, span of the call
+    If(Box<Expr<N>>, Box<Expr<N>>, Box<Expr<N>>), // Condition, then, else
+    Neg(Box<Expr<N>>, Range<usize>),              // Operand, span of '-'
+    Num(N),
+    Op(OpVal, Box<Expr<N>>, Box<Expr<N>>, Range<usize>), // Op, lhs, rhs, span of the operator
+    Var(String, Range<usize>),
+}
+
+// Spans only locate errors, so expressions written differently but parsed the same are equal
+impl<N: Num> PartialEq for Expr<N> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Expr::Eq(lhs1, rhs1, _), Expr::Eq(lhs2, rhs2, _)) => lhs1 == lhs2 && rhs1 == rhs2,
+            (Expr::FuncDef(id1, params1, body1, _), Expr::FuncDef(id2, params2, body2, _)) => {
+                id1 == id2 && params1 == params2 && body1 == body2
+            }
+            (Expr::FuncOrVarMul(id1, args1, _), Expr::FuncOrVarMul(id2, args2, _)) => {
+                id1 == id2 && args1 == args2
+            }
+            (Expr::If(cond1, then1, else1), Expr::If(cond2, then2, else2)) => {
+                cond1 == cond2 && then1 == then2 && else1 == else2
+            }
+            (Expr::Neg(expr1, _), Expr::Neg(expr2, _)) => expr1 == expr2,
+            (Expr::Num(n1), Expr::Num(n2)) => n1 == n2,
+            (Expr::Op(op1, lhs1, rhs1, _), Expr::Op(op2, lhs2, rhs2, _)) => {
+                op1 == op2 && lhs1 == lhs2 && rhs1 == rhs2
+            }
+            (Expr::Var(id1, _), Expr::Var(id2, _)) => id1 == id2,
+            _ => false,
+        }
+    }
+}
+
+// How tightly each level of the grammar binds, from eq_expr to factor
+pub(crate) const EQ: u8 = 0;
+pub(crate) const COND: u8 = 1;
+pub(crate) const OR: u8 = 2;
+pub(crate) const AND: u8 = 3;
+pub(crate) const COMPARE: u8 = 4;
+pub(crate) const CONVERT: u8 = 5;
+pub(crate) const ADD: u8 = 6;
+pub(crate) const MUL: u8 = 7;
+pub(crate) const UNARY: u8 = 8;
+pub(crate) const POW: u8 = 9;
+pub(crate) const FACTOR: u8 = 10;
+
+impl<N: Num + fmt::Display> Expr<N> {
+    pub(crate) fn precedence(&self) -> u8 {
+        match self {
+            Expr::Eq(..) | Expr::FuncDef(..) => EQ,
+            Expr::FuncOrVarMul(..) | Expr::Var(..) => FACTOR,
+            Expr::If(..) => COND,
+            Expr::Neg(..) => UNARY,
+            // numbers like 1/3, 2+3i or 5 m are written with operators, so they are kept together
+            Expr::Num(n) => {
+                let text = n.to_string();
+                let digits = text.strip_prefix('-').unwrap_or(&text);
+                if !digits.chars().all(|c| c.is_ascii_digit() || c == '.') {
+                    EQ
+                } else if digits.len() < text.len() {
+                    UNARY
+                } else {
+                    FACTOR
+                }
+            }
+            Expr::Op(op, ..) => match op {
+                OpVal::Or => OR,
+                OpVal::And => AND,
+                OpVal::In => CONVERT,
+                OpVal::Add | OpVal::Sub => ADD,
+                OpVal::Mul | OpVal::Div | OpVal::Mod => MUL,
+                OpVal::Pow => POW,
+                _ => COMPARE,
+            },
+        }
+    }
+
+    // Writes self, in parentheses if it binds looser than its place in the grammar allows
+    fn write(&self, f: &mut fmt::Formatter, min: u8) -> fmt::Result {
+        if self.precedence() < min {
+            write!(f, "(")?;
+            self.write(f, EQ)?;
+            return write!(f, ")");
+        }
+        match self {
+            Expr::Eq(lhs, rhs, _) => {
+                lhs.write(f, EQ)?;
+                write!(f, " = ")?;
+                rhs.write(f, COND)
+            }
+            Expr::FuncDef(id, params, body, _) => {
+                write!(f, "{}({}) = ", id, params.join(", "))?;
+                body.write(f, COND)
+            }
+            Expr::FuncOrVarMul(id, args, _) => {
+                write!(f, "{}(", id)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    arg.write(f, EQ)?;
+                }
+                write!(f, ")")
+            }
+            Expr::If(cond, then, otherwise) => {
+                cond.write(f, OR)?;
+                write!(f, " ? ")?;
+                then.write(f, EQ)?;
+                write!(f, " : ")?;
+                otherwise.write(f, COND)
+            }
+            Expr::Neg(expr, _) => {
+                write!(f, "-")?;
+                expr.write(f, UNARY)
+            }
+            Expr::Num(n) => write!(f, "{}", n),
+            // '^' is right-associative and takes a unary rhs, like 2^-x
+            Expr::Op(OpVal::Pow, lhs, rhs, _) => {
+                lhs.write(f, FACTOR)?;
+                write!(f, "^")?;
+                rhs.write(f, UNARY)
+            }
+            // the other operators are left-associative
+            Expr::Op(op, lhs, rhs, _) => {
+                let precedence = self.precedence();
+                lhs.write(f, precedence)?;
+                write!(f, " {} ", op.symbol())?;
+                rhs.write(f, precedence + 1)
+            }
+            Expr::Var(id, _) => write!(f, "{}", id),
+        }
+    }
+}
+
+// Writes the expression with as few parentheses as parse needs to read it back the same
+impl<N: Num + fmt::Display> fmt::Display for Expr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, EQ)
+    }
 }
@@ -0,0 +1,275 @@
+use crate::{
+    ensure_arg_count, register_lazy_builtins, InterpretError, Interpreter, Num, NumError, Variant,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+use std::str::FromStr;
+
+// Largest factorial that is computed, only BigInt gets this far without overflowing
+const MAX_FACTORIAL: usize = 10_000;
+
+// Integer exponents are u32, so larger or negative ones cannot be raised to
+fn exponent<N: TryInto<u32>>(other: N) -> Result<u32, NumError> {
+    other.try_into().map_err(|_| NumError::Domain)
+}
+
+// The operators of primitive integers panic or wrap on overflow, the try_* methods never do
+macro_rules! impl_num_int {
+    ($($int:ty)*) => {$(
+        impl Num for $int {
+            #[inline(always)]
+            fn zero() -> Self {
+                0
+            }
+            #[inline(always)]
+            fn one() -> Self {
+                1
+            }
+            #[inline(always)]
+            fn is_whole(&self) -> bool {
+                true
+            }
+            // This panics on negative exponents and overflow, the interpreter uses try_pow
+            fn pow(self, other: Self) -> Self {
+                self.try_pow(other).expect("attempt to raise to a power with overflow")
+            }
+
+            fn try_neg(self) -> Result<Self, NumError> {
+                (0 as $int).checked_sub(self).ok_or(NumError::Overflow)
+            }
+            fn try_add(self, other: Self) -> Result<Self, NumError> {
+                self.checked_add(other).ok_or(NumError::Overflow)
+            }
+            fn try_sub(self, other: Self) -> Result<Self, NumError> {
+                self.checked_sub(other).ok_or(NumError::Overflow)
+            }
+            fn try_mul(self, other: Self) -> Result<Self, NumError> {
+                self.checked_mul(other).ok_or(NumError::Overflow)
+            }
+            fn try_div(self, other: Self) -> Result<Self, NumError> {
+                if other == 0 {
+                    return Err(NumError::Domain);
+                }
+                self.checked_div(other).ok_or(NumError::Overflow)
+            }
+            fn try_rem(self, other: Self) -> Result<Self, NumError> {
+                if other == 0 {
+                    return Err(NumError::Domain);
+                }
+                self.checked_rem(other).ok_or(NumError::Overflow)
+            }
+            fn try_pow(self, other: Self) -> Result<Self, NumError> {
+                self.checked_pow(exponent(other)?).ok_or(NumError::Overflow)
+            }
+        }
+
+        impl Default for Interpreter<$int> {
+            fn default() -> Self {
+                let mut interpreter = Interpreter::new();
+                register_int_builtins(&mut interpreter.vars);
+                interpreter
+            }
+        }
+    )*};
+}
+
+impl_num_int!(i64 i128);
+
+// A u64, which can only be a Num as a wrapper, because Num requires Neg.
+// Like the other integer operators, negating anything but 0 panics, while try_neg is a domain error.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Unsigned(pub u64);
+
+impl FromStr for Unsigned {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Unsigned)
+    }
+}
+
+impl fmt::Display for Unsigned {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+macro_rules! impl_op {
+    ($($trait:ident $method:ident $assign_trait:ident $assign_method:ident),*) => {$(
+        impl $trait for Unsigned {
+            type Output = Unsigned;
+
+            fn $method(self, other: Unsigned) -> Unsigned {
+                Unsigned(self.0.$method(other.0))
+            }
+        }
+
+        impl $assign_trait for Unsigned {
+            fn $assign_method(&mut self, other: Unsigned) {
+                self.0.$assign_method(other.0)
+            }
+        }
+    )*};
+}
+
+impl_op!(
+    Add add AddAssign add_assign,
+    Sub sub SubAssign sub_assign,
+    Mul mul MulAssign mul_assign,
+    Div div DivAssign div_assign,
+    Rem rem RemAssign rem_assign
+);
+
+impl Neg for Unsigned {
+    type Output = Unsigned;
+
+    fn neg(self) -> Unsigned {
+        self.try_neg()
+            .expect("attempt to negate a non-zero unsigned integer")
+    }
+}
+
+impl Num for Unsigned {
+    #[inline(always)]
+    fn zero() -> Self {
+        Unsigned(0)
+    }
+    #[inline(always)]
+    fn one() -> Self {
+        Unsigned(1)
+    }
+    #[inline(always)]
+    fn is_whole(&self) -> bool {
+        true
+    }
+    // This panics on overflow, the interpreter uses try_pow
+    fn pow(self, other: Self) -> Self {
+        self.try_pow(other)
+            .expect("attempt to raise to a power with overflow")
+    }
+
+    // Only 0 has a negation
+    fn try_neg(self) -> Result<Self, NumError> {
+        if self.0 == 0 {
+            Ok(self)
+        } else {
+            Err(NumError::Domain)
+        }
+    }
+    fn try_add(self, other: Self) -> Result<Self, NumError> {
+        self.0
+            .checked_add(other.0)
+            .map(Unsigned)
+            .ok_or(NumError::Overflow)
+    }
+    fn try_sub(self, other: Self) -> Result<Self, NumError> {
+        self.0
+            .checked_sub(other.0)
+            .map(Unsigned)
+            .ok_or(NumError::Overflow)
+    }
+    fn try_mul(self, other: Self) -> Result<Self, NumError> {
+        self.0
+            .checked_mul(other.0)
+            .map(Unsigned)
+            .ok_or(NumError::Overflow)
+    }
+    fn try_div(self, other: Self) -> Result<Self, NumError> {
+        self.0
+            .checked_div(other.0)
+            .map(Unsigned)
+            .ok_or(NumError::Domain)
+    }
+    fn try_rem(self, other: Self) -> Result<Self, NumError> {
+        self.0
+            .checked_rem(other.0)
+            .map(Unsigned)
+            .ok_or(NumError::Domain)
+    }
+    fn try_pow(self, other: Self) -> Result<Self, NumError> {
+        self.0
+            .checked_pow(exponent(other.0)?)
+            .map(Unsigned)
+            .ok_or(NumError::Overflow)
+    }
+}
+
+impl Default for Interpreter<Unsigned> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        register_int_builtins(&mut interpreter.vars);
+        interpreter
+    }
+}
+
+// Builtins shared by every integer type
+pub(crate) fn register_int_builtins<N: Num + Ord>(vars: &mut HashMap<String, Variant<N>>) {
+    vars.insert(
+        String::from("abs"),
+        Variant::Function(|id, args| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let x = args[0].clone();
+            if x < N::zero() {
+                x.try_neg()
+                    .map_err(|err| InterpretError::from_num_error(err, id))
+            } else {
+                Ok(x)
+            }
+        }),
+    );
+    vars.insert(
+        String::from("sign"),
+        Variant::Function(|id, args| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            Ok(if args[0] < N::zero() {
+                N::zero() - N::one()
+            } else if args[0] == N::zero() {
+                N::zero()
+            } else {
+                N::one()
+            })
+        }),
+    );
+    vars.insert(
+        String::from("min"),
+        Variant::Function(|id, args| {
+            ensure_arg_count(1, usize::MAX, args.len(), id)?;
+            Ok(args.iter().min().unwrap().clone())
+        }),
+    );
+    vars.insert(
+        String::from("max"),
+        Variant::Function(|id, args| {
+            ensure_arg_count(1, usize::MAX, args.len(), id)?;
+            Ok(args.iter().max().unwrap().clone())
+        }),
+    );
+    register_lazy_builtins(vars);
+    vars.insert(
+        String::from("factorial"),
+        Variant::Function(|id, args| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            let n = &args[0];
+            if *n < N::zero() {
+                return Err(InterpretError::DomainError(id.into()));
+            }
+            let mut result = N::one();
+            let mut i = N::one();
+            let mut steps = 0;
+            while i < *n {
+                steps += 1;
+                if steps > MAX_FACTORIAL {
+                    return Err(InterpretError::Overflow(id.into()));
+                }
+                i += N::one();
+                result = result
+                    .try_mul(i.clone())
+                    .map_err(|err| InterpretError::from_num_error(err, id))?;
+            }
+            Ok(result)
+        }),
+    );
+}
@@ -1,25 +1,163 @@
-use crate::{Expr, Num, OpVal};
+use crate::{parse, tokenize, Error, ErrorKind, Expr, Num, NumError, OpVal};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
+use std::str::FromStr;
+use std::sync::Arc;
+
+// How deeply user functions may call each other before giving up
+const MAX_CALL_DEPTH: usize = 256;
+// The largest n whose factorial fits in an f64
+pub(crate) const MAX_FACTORIAL: f64 = 170.0;
+// How many terms sigma(i, a, b, expr) may add up before giving up
+const MAX_SUM_TERMS: usize = 1_000_000;
+// How many subintervals integrate splits its range into, which must be even
+const SIMPSON_INTERVALS: usize = 1024;
+
+// An error and the span of the expression it came from
+type Spanned<'expr> = (InterpretError<'expr>, Range<usize>);
 
 #[derive(Clone)]
 pub enum Variant<N: Num> {
     Num(N),
     Function(for<'expr> fn(&'expr str, &[N]) -> Result<N, InterpretError<'expr>>),
+    UserFunction(Vec<String>, Arc<Expr<N>>), // Params, body
+    // A function that also receives the interpreter's angle mode, such as sin
+    AngleFunction(for<'expr> fn(&'expr str, &[N], AngleMode) -> Result<N, InterpretError<'expr>>),
+    // A function that receives its arguments unevaluated, such as if or sum(i, 1, 10, i^2)
+    LazyFunction(LazyFn<N>),
+    // A native function that can capture state, such as a lookup table or a configured rate
+    Closure(Arc<ClosureFn<N>>),
+}
+
+pub type LazyFn<N> = for<'expr> fn(
+    &'expr str,
+    &'expr [Expr<N>],
+    &mut Interpreter<N>,
+) -> Result<N, InterpretError<'expr>>;
+
+pub type ClosureFn<N> =
+    dyn for<'expr> Fn(&'expr str, &[N]) -> Result<N, InterpretError<'expr>> + Send + Sync;
+
+impl<N: Num> Variant<N> {
+    // Wraps a closure, so it can be passed to Interpreter::set_var
+    pub fn closure<F>(func: F) -> Variant<N>
+    where
+        F: for<'expr> Fn(&'expr str, &[N]) -> Result<N, InterpretError<'expr>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Variant::Closure(Arc::new(func))
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum AngleMode {
+    #[default]
+    Radians,
+    Degrees,
+    Gradians,
+}
+
+impl AngleMode {
+    pub fn to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            AngleMode::Degrees => angle.to_radians(),
+            AngleMode::Gradians => angle * (std::f64::consts::PI / 200.0),
+        }
+    }
+
+    pub fn from_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleMode::Radians => angle,
+            AngleMode::Degrees => angle.to_degrees(),
+            AngleMode::Gradians => angle * (200.0 / std::f64::consts::PI),
+        }
+    }
+}
+
+impl FromStr for AngleMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rad" | "radians" => Ok(AngleMode::Radians),
+            "deg" | "degrees" => Ok(AngleMode::Degrees),
+            "grad" | "gradians" => Ok(AngleMode::Gradians),
+            _ => Err(format!(
+                "unknown angle mode {:?}, expected rad, deg or grad",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum InterpretError<'expr> {
-    TooFewArgs(&'expr str, usize),  // Id of function, min args
-    TooManyArgs(&'expr str, usize), // Id of function, max args
-    VarDoesNotExist(&'expr str),
-    VarIsNotFunction(&'expr str),
-    FunctionNameUsedLikeVar(&'expr str),
+    TooFewArgs(Cow<'expr, str>, usize),  // Id of function, min args
+    TooManyArgs(Cow<'expr, str>, usize), // Id of function, max args
+    VarDoesNotExist(Cow<'expr, str>),
+    VarIsNotFunction(Cow<'expr, str>),
+    FunctionNameUsedLikeVar(Cow<'expr, str>),
+    NoUnknown,
+    MultipleUnknowns(Cow<'expr, str>, Cow<'expr, str>), // Ids of the first two unknowns
+    NoSolution(Cow<'expr, str>),                        // Id of the unknown
+    InfiniteSolutions(Cow<'expr, str>),                 // Id of the unknown
+    RecursionLimit(Cow<'expr, str>),                    // Id of function
+    DivisionByZero,
+    DomainError(Cow<'expr, str>), // Id of function or operator symbol
+    Overflow(Cow<'expr, str>),    // Id of function or operator symbol
+    DimensionMismatch(Cow<'expr, str>), // Id of function or operator symbol
+    NotDifferentiable(Cow<'expr, str>), // Id of function or operator symbol
+    NotCompilable(Cow<'expr, str>), // Id of function or operator symbol
+}
+
+impl<'expr> InterpretError<'expr> {
+    pub(crate) fn from_num_error(err: NumError, name: &'expr str) -> InterpretError<'expr> {
+        match err {
+            NumError::Overflow => InterpretError::Overflow(name.into()),
+            NumError::Domain => InterpretError::DomainError(name.into()),
+            NumError::Dimension => InterpretError::DimensionMismatch(name.into()),
+        }
+    }
+
+    pub fn into_owned(self) -> InterpretError<'static> {
+        fn owned(id: Cow<str>) -> Cow<'static, str> {
+            Cow::Owned(id.into_owned())
+        }
+        match self {
+            InterpretError::TooFewArgs(id, n) => InterpretError::TooFewArgs(owned(id), n),
+            InterpretError::TooManyArgs(id, n) => InterpretError::TooManyArgs(owned(id), n),
+            InterpretError::VarDoesNotExist(id) => InterpretError::VarDoesNotExist(owned(id)),
+            InterpretError::VarIsNotFunction(id) => InterpretError::VarIsNotFunction(owned(id)),
+            InterpretError::FunctionNameUsedLikeVar(id) => {
+                InterpretError::FunctionNameUsedLikeVar(owned(id))
+            }
+            InterpretError::NoUnknown => InterpretError::NoUnknown,
+            InterpretError::MultipleUnknowns(id1, id2) => {
+                InterpretError::MultipleUnknowns(owned(id1), owned(id2))
+            }
+            InterpretError::NoSolution(id) => InterpretError::NoSolution(owned(id)),
+            InterpretError::InfiniteSolutions(id) => InterpretError::InfiniteSolutions(owned(id)),
+            InterpretError::RecursionLimit(id) => InterpretError::RecursionLimit(owned(id)),
+            InterpretError::DivisionByZero => InterpretError::DivisionByZero,
+            InterpretError::DomainError(id) => InterpretError::DomainError(owned(id)),
+            InterpretError::Overflow(id) => InterpretError::Overflow(owned(id)),
+            InterpretError::DimensionMismatch(id) => InterpretError::DimensionMismatch(owned(id)),
+            InterpretError::NotDifferentiable(id) => InterpretError::NotDifferentiable(owned(id)),
+            InterpretError::NotCompilable(id) => InterpretError::NotCompilable(owned(id)),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct Interpreter<N: Num> {
     pub vars: HashMap<String, Variant<N>>,
+    pub strict: bool, // Whether NaN and infinite results are errors
+    pub angle_mode: AngleMode,
+    call_depth: usize,
 }
 
 impl<N: Num> Interpreter<N> {
@@ -27,6 +165,9 @@ impl<N: Num> Interpreter<N> {
     pub fn new() -> Interpreter<N> {
         Interpreter {
             vars: HashMap::new(),
+            strict: false,
+            angle_mode: AngleMode::Radians,
+            call_depth: 0,
         }
     }
 
@@ -41,12 +182,31 @@ impl<N: Num> Interpreter<N> {
     }
 
     pub fn eval<'expr>(&mut self, expr: &'expr Expr<N>) -> Result<N, InterpretError<'expr>> {
+        self.eval_node(expr).map_err(|(err, _)| err)
+    }
+
+    // Like eval, but the error also has the span of the call or operator that failed
+    pub fn eval_spanned(&mut self, expr: &Expr<N>) -> Result<N, Error> {
+        self.eval_node(expr).map_err(|(err, span)| Error {
+            kind: ErrorKind::Interpret(err.into_owned()),
+            span,
+        })
+    }
+
+    // Tokenizes, parses and evaluates input in one step
+    pub fn eval_str(&mut self, input: &str) -> Result<N, Error> {
+        let tokens = tokenize(input)?;
+        let expr = parse(&tokens).map_err(|err| Error::from_parse(err, input))?;
+        self.eval_spanned(&expr)
+    }
+
+    fn eval_node<'expr>(&mut self, expr: &'expr Expr<N>) -> Result<N, Spanned<'expr>> {
         // simple, naive recursive tree walk
         match expr {
-            Expr::Eq(lhs, rhs) => match lhs.deref() {
-                Expr::Var(id) => {
-                    let result = self.eval(rhs)?;
-                    if let Some(val) = self.vars.get_mut(*id) {
+            Expr::Eq(lhs, rhs, span) => match lhs.deref() {
+                Expr::Var(id, _) => {
+                    let result = self.eval_node(rhs)?;
+                    if let Some(val) = self.vars.get_mut(id) {
                         *val = Variant::Num(result.clone());
                     } else {
                         self.vars
@@ -54,57 +214,207 @@ impl<N: Num> Interpreter<N> {
                     }
                     Ok(result)
                 }
-                _ => todo!("implement algebra solving"),
+                _ => self.solve(lhs, rhs).map_err(|err| (err, span.clone())),
+            },
+            Expr::FuncDef(id, params, body, span) => match self.vars.get(id) {
+                // only user functions are redefined, sqrt(x) = 3 is an equation
+                Some(var) if !matches!(var, Variant::UserFunction(..)) => {
+                    let args = params
+                        .iter()
+                        .map(|param| Expr::Var(param.clone(), span.clone()))
+                        .collect();
+                    let call = Expr::FuncOrVarMul(id.clone(), args, span.clone());
+                    self.solve(&call, body)
+                        .map_err(|err| (err.into_owned(), span.clone()))
+                }
+                _ => {
+                    let body = Arc::new(body.deref().clone());
+                    self.vars
+                        .insert(id.clone(), Variant::UserFunction(params.clone(), body));
+                    Ok(N::zero())
+                }
             },
-            Expr::FuncOrVarMul(id, exprs) => {
+            Expr::FuncOrVarMul(id, exprs, span) => {
+                if let Some(Variant::LazyFunction(func)) = self.vars.get(id) {
+                    let func = *func;
+                    return func(id, exprs, self)
+                        .and_then(|result| self.check_result(result, id))
+                        .map_err(|err| (err, span.clone()));
+                }
                 let mut args = Vec::with_capacity(exprs.len());
                 for expr in exprs {
-                    args.push(self.eval(expr)?);
+                    args.push(self.eval_node(expr)?);
                 }
 
-                if let Some(var) = self.vars.get(*id) {
+                let result = if let Some(var) = self.vars.get(id) {
                     match var {
                         Variant::Num(n) => {
                             if args.len() == 1 {
+                                // x(4) is x * 4, which fails like the operator does
                                 let arg = args.remove(0);
-                                Ok(n.clone().mul(arg))
+                                n.clone()
+                                    .try_mul(arg)
+                                    .map_err(|err| InterpretError::from_num_error(err, "*"))
                             } else {
-                                Err(InterpretError::VarIsNotFunction(id))
+                                Err(InterpretError::VarIsNotFunction(id.as_str().into()))
                             }
                         }
                         Variant::Function(func) => func(id, &args),
+                        Variant::UserFunction(params, body) => {
+                            let (params, body) = (params.clone(), body.clone());
+                            self.call_user_function(id, &params, &body, args)
+                        }
+                        Variant::AngleFunction(func) => func(id, &args, self.angle_mode),
+                        Variant::LazyFunction(_) => unreachable!(),
+                        Variant::Closure(func) => func(id, &args),
                     }
                 } else {
-                    Err(InterpretError::VarDoesNotExist(id))
+                    Err(InterpretError::VarDoesNotExist(id.as_str().into()))
+                };
+                result
+                    .and_then(|result| self.check_result(result, id))
+                    .map_err(|err| (err, span.clone()))
+            }
+            // only the chosen branch is evaluated
+            Expr::If(cond, then, otherwise) => {
+                if self.eval_node(cond)? != N::zero() {
+                    self.eval_node(then)
+                } else {
+                    self.eval_node(otherwise)
+                }
+            }
+            Expr::Neg(expr, span) => self
+                .eval_node(expr)?
+                .try_neg()
+                .map_err(|err| (InterpretError::from_num_error(err, "-"), span.clone())),
+            Expr::Num(n) => Ok(n.clone()),
+            Expr::Op(op @ (OpVal::And | OpVal::Or), lhs, rhs, _) => {
+                // rhs is only evaluated if lhs does not decide the result
+                let lhs = self.eval_node(lhs)? != N::zero();
+                if lhs == (*op == OpVal::Or) {
+                    return Ok(truth(lhs));
                 }
+                Ok(truth(self.eval_node(rhs)? != N::zero()))
             }
-            Expr::Neg(expr) => Ok(-self.eval(expr)?),
-            Expr::Num(n) => Ok((*n).clone()),
-            Expr::Op(op, lhs, rhs) => {
-                let lhs = self.eval(lhs)?;
-                let rhs = self.eval(rhs)?;
-                Ok(match op {
-                    OpVal::Add => lhs + rhs,
-                    OpVal::Sub => lhs - rhs,
-                    OpVal::Mul => lhs * rhs,
-                    OpVal::Div => lhs / rhs,
-                    OpVal::Mod => lhs % rhs,
-                    OpVal::Pow => lhs.pow(rhs),
+            Expr::Op(op, lhs, rhs, span) => {
+                let lhs = self.eval_node(lhs)?;
+                let rhs = self.eval_node(rhs)?;
+                if (*op == OpVal::Div || *op == OpVal::Mod || *op == OpVal::In) && rhs == N::zero()
+                {
+                    return Err((InterpretError::DivisionByZero, span.clone()));
+                }
+                let (result, symbol) = match op {
+                    OpVal::Add => (lhs.try_add(rhs), "+"),
+                    OpVal::Sub => (lhs.try_sub(rhs), "-"),
+                    OpVal::Mul => (lhs.try_mul(rhs), "*"),
+                    OpVal::Div => (lhs.try_div(rhs), "/"),
+                    OpVal::Mod => (lhs.try_rem(rhs), "%"),
+                    OpVal::Pow => (lhs.try_pow(rhs), "^"),
+                    OpVal::In => (lhs.try_convert(rhs), "in"),
+                    OpVal::Less => (Ok(truth(lhs < rhs)), "<"),
+                    OpVal::LessEq => (Ok(truth(lhs <= rhs)), "<="),
+                    OpVal::Greater => (Ok(truth(lhs > rhs)), ">"),
+                    OpVal::GreaterEq => (Ok(truth(lhs >= rhs)), ">="),
+                    OpVal::Equal => (Ok(truth(lhs == rhs)), "=="),
+                    OpVal::NotEqual => (Ok(truth(lhs != rhs)), "!="),
                     _ => unreachable!(),
-                })
+                };
+                result
+                    .map_err(|err| InterpretError::from_num_error(err, symbol))
+                    .and_then(|result| self.check_result(result, symbol))
+                    .map_err(|err| (err, span.clone()))
             }
-            Expr::Var(id) => {
-                if let Some(var) = self.vars.get(*id) {
+            Expr::Var(id, span) => {
+                if let Some(var) = self.vars.get(id) {
                     match var {
                         Variant::Num(n) => Ok(n.clone()),
-                        Variant::Function(_) => Err(InterpretError::FunctionNameUsedLikeVar(id)),
+                        Variant::Function(_)
+                        | Variant::UserFunction(..)
+                        | Variant::AngleFunction(_)
+                        | Variant::LazyFunction(_)
+                        | Variant::Closure(_) => Err((
+                            InterpretError::FunctionNameUsedLikeVar(id.as_str().into()),
+                            span.clone(),
+                        )),
                     }
                 } else {
-                    Err(InterpretError::VarDoesNotExist(id))
+                    Err((
+                        InterpretError::VarDoesNotExist(id.as_str().into()),
+                        span.clone(),
+                    ))
                 }
             }
         }
     }
+
+    // Evaluates expr with id bound to value, for lazy functions such as sum(i, 1, 10, i^2)
+    pub fn eval_with_var<'expr>(
+        &mut self,
+        id: &str,
+        value: N,
+        expr: &'expr Expr<N>,
+    ) -> Result<N, InterpretError<'expr>> {
+        let shadowed = self.vars.insert(id.to_string(), Variant::Num(value));
+        let result = self.eval(expr);
+        match shadowed {
+            Some(var) => self.vars.insert(id.to_string(), var),
+            None => self.vars.remove(id),
+        };
+        result
+    }
+
+    // In strict mode, NaN and infinite results are errors instead of values
+    fn check_result<'expr>(&self, result: N, name: &'expr str) -> Result<N, InterpretError<'expr>> {
+        if self.strict && result.is_nan() {
+            Err(InterpretError::DomainError(name.into()))
+        } else if self.strict && result.is_infinite() {
+            Err(InterpretError::Overflow(name.into()))
+        } else {
+            Ok(result)
+        }
+    }
+
+    fn call_user_function<'expr>(
+        &mut self,
+        id: &'expr str,
+        params: &[String],
+        body: &Expr<N>,
+        args: Vec<N>,
+    ) -> Result<N, InterpretError<'expr>> {
+        ensure_arg_count(params.len(), params.len(), args.len(), id)?;
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(InterpretError::RecursionLimit(id.into()));
+        }
+
+        // bind the arguments, remembering what the params shadowed
+        let shadowed: Vec<Option<Variant<N>>> = params
+            .iter()
+            .zip(args)
+            .map(|(param, arg)| self.vars.insert(param.clone(), Variant::Num(arg)))
+            .collect();
+
+        self.call_depth += 1;
+        // the body is only borrowed for this call, so errors from it must own their ids
+        let result = self.eval(body).map_err(InterpretError::into_owned);
+        self.call_depth -= 1;
+
+        for (param, shadowed) in params.iter().zip(shadowed).rev() {
+            match shadowed {
+                Some(var) => self.vars.insert(param.clone(), var),
+                None => self.vars.remove(param),
+            };
+        }
+        result
+    }
+}
+
+// Comparisons and logic operators return 1 for true and 0 for false
+pub(crate) fn truth<N: Num>(b: bool) -> N {
+    if b {
+        N::one()
+    } else {
+        N::zero()
+    }
 }
 
 #[inline]
@@ -113,16 +423,109 @@ pub fn ensure_arg_count(
     max: usize,
     args_len: usize,
     func_id: &str,
-) -> Result<(), InterpretError> {
+) -> Result<(), InterpretError<'_>> {
     if args_len < min {
-        Err(InterpretError::TooFewArgs(func_id, min))
+        Err(InterpretError::TooFewArgs(func_id.into(), min))
     } else if args_len > max {
-        Err(InterpretError::TooManyArgs(func_id, max))
+        Err(InterpretError::TooManyArgs(func_id.into(), max))
     } else {
         Ok(())
     }
 }
 
+// The builtins every backend shares, which are sum and the ones that receive their arguments unevaluated
+pub fn register_lazy_builtins<N: Num>(vars: &mut HashMap<String, Variant<N>>) {
+    vars.insert(
+        String::from("sum"),
+        Variant::Function(|id, args| {
+            args.iter().try_fold(N::zero(), |acc, x| {
+                acc.try_add(x.clone())
+                    .map_err(|err| InterpretError::from_num_error(err, id))
+            })
+        }),
+    );
+    vars.insert(
+        String::from("sigma"),
+        Variant::LazyFunction(|id, exprs, interpreter| {
+            // sigma(i, a, b, expr) adds up expr for i = a, a + 1, .., b
+            ensure_arg_count(4, 4, exprs.len(), id)?;
+            let Expr::Var(var, _) = &exprs[0] else {
+                return Err(InterpretError::DomainError(id.into()));
+            };
+            let mut i = interpreter.eval(&exprs[1])?;
+            let to = interpreter.eval(&exprs[2])?;
+            let mut acc = N::zero();
+            let mut terms = 0;
+            while i <= to {
+                if terms == MAX_SUM_TERMS {
+                    return Err(InterpretError::Overflow(id.into()));
+                }
+                let term = interpreter.eval_with_var(var, i.clone(), &exprs[3])?;
+                acc = acc
+                    .try_add(term)
+                    .map_err(|err| InterpretError::from_num_error(err, id))?;
+                i = i
+                    .try_add(N::one())
+                    .map_err(|err| InterpretError::from_num_error(err, id))?;
+                terms += 1;
+            }
+            Ok(acc)
+        }),
+    );
+}
+
+// Registers a one argument function, checking that its argument is within the domain
+macro_rules! unary_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        unary_fn!($vars, $name, $func, |_| true)
+    };
+    ($vars:ident, $name:literal, $func:expr, $domain:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                if !$domain(args[0]) {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                Ok($func(args[0]))
+            }),
+        )
+    };
+}
+pub(crate) use unary_fn;
+
+// Registers a trigonometric function, converting its argument from the angle mode to radians
+macro_rules! trig_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::AngleFunction(|id, args, mode| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                Ok($func(mode.to_radians(args[0])))
+            }),
+        )
+    };
+}
+
+// Registers an inverse trigonometric function, converting its result from radians to the angle mode
+macro_rules! inverse_trig_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        inverse_trig_fn!($vars, $name, $func, |_| true)
+    };
+    ($vars:ident, $name:literal, $func:expr, $domain:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::AngleFunction(|id, args, mode| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                if !$domain(args[0]) {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                Ok(mode.from_radians($func(args[0])))
+            }),
+        )
+    };
+}
+
 impl Default for Interpreter<f64> {
     fn default() -> Self {
         let mut vars = HashMap::new();
@@ -134,32 +537,138 @@ impl Default for Interpreter<f64> {
         vars.insert(String::from("planck"), Variant::Num(6.62607015e-34)); // planck constant in J/Hz
         vars.insert(String::from("planckbar"), Variant::Num(1.054571817e-34)); // reduced planck constant in Js
         vars.insert(String::from("gravconst"), Variant::Num(6.67430e-11)); // gravitational constant in m^3/kg/s^2
+
+        // roots and rounding
+        unary_fn!(vars, "abs", f64::abs);
+        unary_fn!(vars, "sqrt", f64::sqrt, |x| x >= 0.0);
+        unary_fn!(vars, "cbrt", f64::cbrt);
+        unary_fn!(vars, "floor", f64::floor);
+        unary_fn!(vars, "ceil", f64::ceil);
+        unary_fn!(vars, "round", f64::round);
+        unary_fn!(vars, "trunc", f64::trunc);
+        unary_fn!(vars, "sign", |x: f64| if x == 0.0 {
+            0.0
+        } else {
+            x.signum()
+        });
+
+        // trigonometry
+        trig_fn!(vars, "sin", f64::sin);
+        trig_fn!(vars, "cos", f64::cos);
+        trig_fn!(vars, "tan", f64::tan);
+        trig_fn!(vars, "sec", |x: f64| 1.0 / x.cos());
+        trig_fn!(vars, "csc", |x: f64| 1.0 / x.sin());
+        trig_fn!(vars, "cot", |x: f64| 1.0 / x.tan());
+        inverse_trig_fn!(vars, "asin", f64::asin, |x| (-1.0..=1.0).contains(&x));
+        inverse_trig_fn!(vars, "acos", f64::acos, |x| (-1.0..=1.0).contains(&x));
+        inverse_trig_fn!(vars, "atan", f64::atan);
         vars.insert(
-            String::from("abs"),
-            Variant::Function(|id, args| {
+            String::from("atan2"),
+            Variant::AngleFunction(|id, args, mode| {
+                ensure_arg_count(2, 2, args.len(), id)?;
+                Ok(mode.from_radians(args[0].atan2(args[1])))
+            }),
+        );
+        vars.insert(
+            String::from("deg"),
+            Variant::AngleFunction(|id, args, mode| {
+                // converts degrees to the angle mode, this is what 90° and 90deg mean
                 ensure_arg_count(1, 1, args.len(), id)?;
-                Ok(args[0].abs())
+                Ok(mode.from_radians(args[0].to_radians()))
             }),
         );
+        unary_fn!(vars, "sinh", f64::sinh);
+        unary_fn!(vars, "cosh", f64::cosh);
+        unary_fn!(vars, "tanh", f64::tanh);
+        unary_fn!(vars, "asinh", f64::asinh);
+        unary_fn!(vars, "acosh", f64::acosh, |x| x >= 1.0);
+        unary_fn!(vars, "atanh", f64::atanh, |x| -1.0 < x && x < 1.0);
+
+        // exponentials and logarithms
+        unary_fn!(vars, "exp", f64::exp);
+        unary_fn!(vars, "ln", f64::ln, |x| x > 0.0);
+        unary_fn!(vars, "log2", f64::log2, |x| x > 0.0);
+        unary_fn!(vars, "log10", f64::log10, |x| x > 0.0);
         vars.insert(
-            String::from("sqrt"),
+            String::from("log"),
             Variant::Function(|id, args| {
-                ensure_arg_count(1, 1, args.len(), id)?;
-                Ok(args[0].sqrt())
+                // log(x) is base 10, log(x, base) is any other base
+                ensure_arg_count(1, 2, args.len(), id)?;
+                let base = args.get(1).copied().unwrap_or(10.0);
+                if args[0] <= 0.0 || base <= 0.0 || base == 1.0 {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                Ok(args[0].log(base))
+            }),
+        );
+
+        // variadic
+        vars.insert(
+            String::from("min"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, usize::MAX, args.len(), id)?;
+                Ok(args.iter().copied().fold(f64::INFINITY, f64::min))
+            }),
+        );
+        vars.insert(
+            String::from("max"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, usize::MAX, args.len(), id)?;
+                Ok(args.iter().copied().fold(f64::NEG_INFINITY, f64::max))
+            }),
+        );
+        register_lazy_builtins(&mut vars);
+        vars.insert(
+            String::from("integrate"),
+            Variant::LazyFunction(|id, exprs, interpreter| {
+                // integrate(expr, x, a, b) with Simpson's rule
+                ensure_arg_count(4, 4, exprs.len(), id)?;
+                let Expr::Var(var, _) = &exprs[1] else {
+                    return Err(InterpretError::DomainError(id.into()));
+                };
+                let a = interpreter.eval(&exprs[2])?;
+                let b = interpreter.eval(&exprs[3])?;
+                let h = (b - a) / SIMPSON_INTERVALS as f64;
+                let mut acc = 0.0;
+                for k in 0..=SIMPSON_INTERVALS {
+                    let weight = match k {
+                        0 | SIMPSON_INTERVALS => 1.0,
+                        _ if k % 2 == 1 => 4.0,
+                        _ => 2.0,
+                    };
+                    acc += weight * interpreter.eval_with_var(var, a + k as f64 * h, &exprs[0])?;
+                }
+                Ok(acc * h / 3.0)
+            }),
+        );
+        vars.insert(
+            String::from("hypot"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, usize::MAX, args.len(), id)?;
+                Ok(args.iter().copied().fold(0.0, f64::hypot))
             }),
         );
+
         vars.insert(
             String::from("factorial"),
             Variant::Function(|id, args| {
                 ensure_arg_count(1, 1, args.len(), id)?;
                 let n = args[0];
-                if n <= 1.0 {
-                    Ok(1.0)
-                } else {
-                    Ok((1..=n as u64).product::<u64>() as f64)
+                if n < 0.0 || n.fract() != 0.0 {
+                    return Err(InterpretError::DomainError(id.into()));
                 }
+                // larger n overflow, however long the loop up to them would take
+                if n > MAX_FACTORIAL {
+                    return Err(InterpretError::Overflow(id.into()));
+                }
+                Ok((2..=n as u64).fold(1.0, |acc, i| acc * i as f64))
             }),
         );
-        Interpreter { vars }
+        Interpreter {
+            vars,
+            strict: false,
+            angle_mode: AngleMode::Radians,
+            call_depth: 0,
+        }
     }
 }
@@ -0,0 +1,620 @@
+use crate::{
+    ensure_arg_count, register_lazy_builtins, AngleMode, InterpretError, Interpreter, Num,
+    NumError, Variant,
+};
+use std::cmp::Ordering;
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+// Library functions like sin and exp are not always correctly rounded, so their bounds are widened further
+const LIBM_ULPS: usize = 2;
+
+// 2^53, whole numbers up to it are exact in an f64, and larger ones are rounded when parsed
+const MAX_EXACT: f64 = 9_007_199_254_740_992.0;
+
+// 2^-969, the rounding error of a smaller product or quotient can be too small for an f64
+const TINY: f64 = 2.004_168_360_008_973e-292;
+
+// Bounds of a rounded result given the sign of its exact rounding error,
+// so exact results like 1 + 2 stay points. A NaN error is unknown and always moves the bound.
+fn down(x: f64, err: f64) -> f64 {
+    if err < 0.0 || err.is_nan() {
+        x.next_down()
+    } else {
+        x
+    }
+}
+
+fn up(x: f64, err: f64) -> f64 {
+    if err > 0.0 || err.is_nan() {
+        x.next_up()
+    } else {
+        x
+    }
+}
+
+// a + b and its exact rounding error
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let sum = a + b;
+    let b_part = sum - a;
+    (sum, (a - (sum - b_part)) + (b - b_part))
+}
+
+// a * b and its exact rounding error
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let product = a * b;
+    if product.abs() < TINY && a != 0.0 && b != 0.0 {
+        return (product, f64::NAN);
+    }
+    (product, a.mul_add(b, -product))
+}
+
+// a / b and a rounding error with the same sign as the exact one
+fn two_quotient(a: f64, b: f64) -> (f64, f64) {
+    let quotient = a / b;
+    if quotient.abs() < TINY && a != 0.0 {
+        return (quotient, f64::NAN);
+    }
+    // a - quotient * b is exact
+    let remainder = -quotient.mul_add(b, -a);
+    (quotient, remainder * b.signum())
+}
+
+// x^n for positive x by squaring, rounding every step down for the lower bound and up for the upper one
+fn pow_bounds(x: f64, n: u32) -> (f64, f64) {
+    let (mut lo, mut hi) = (1.0, 1.0);
+    let (mut base_lo, mut base_hi) = (x, x);
+    let mut n = n;
+    while n > 0 {
+        if n % 2 == 1 {
+            let ((l, l_err), (h, h_err)) = (two_product(lo, base_lo), two_product(hi, base_hi));
+            (lo, hi) = (down(l, l_err), up(h, h_err));
+        }
+        let ((l, l_err), (h, h_err)) =
+            (two_product(base_lo, base_lo), two_product(base_hi, base_hi));
+        (base_lo, base_hi) = (down(l, l_err), up(h, h_err));
+        n /= 2;
+    }
+    (lo, hi)
+}
+
+// A closed range of reals that is guaranteed to contain the exact result.
+// Every operation rounds its lower bound down and its upper bound up.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParseIntervalError;
+
+impl Interval {
+    pub const ENTIRE: Interval = Interval {
+        lo: f64::NEG_INFINITY,
+        hi: f64::INFINITY,
+    };
+
+    #[inline(always)]
+    pub fn new(lo: f64, hi: f64) -> Interval {
+        Interval { lo, hi }
+    }
+
+    #[inline(always)]
+    pub fn point(x: f64) -> Interval {
+        Interval { lo: x, hi: x }
+    }
+
+    // The interval around a rounded result, which is at most `ulps` away from the exact one
+    fn widened(lo: f64, hi: f64, ulps: usize) -> Interval {
+        let (mut lo, mut hi) = (lo, hi);
+        for _ in 0..ulps {
+            lo = lo.next_down();
+            hi = hi.next_up();
+        }
+        Interval { lo, hi }
+    }
+
+    // The interval around values that were rounded to nearest, so one ulp is enough
+    fn rounded(lo: f64, hi: f64) -> Interval {
+        Interval::widened(lo, hi, 1)
+    }
+
+    // The bounds of all results of a basic operation over the bounds of two intervals
+    fn corners(self, other: Interval, op: fn(f64, f64) -> (f64, f64)) -> Interval {
+        let corners = [
+            op(self.lo, other.lo),
+            op(self.lo, other.hi),
+            op(self.hi, other.lo),
+            op(self.hi, other.hi),
+        ];
+        if corners.iter().any(|(x, _)| x.is_nan()) {
+            return Interval::nan();
+        }
+        let lo = corners
+            .iter()
+            .map(|&(x, err)| down(x, err))
+            .fold(f64::INFINITY, f64::min);
+        let hi = corners
+            .iter()
+            .map(|&(x, err)| up(x, err))
+            .fold(f64::NEG_INFINITY, f64::max);
+        Interval::new(lo, hi)
+    }
+
+    fn nan() -> Interval {
+        Interval::point(f64::NAN)
+    }
+
+    pub fn midpoint(self) -> f64 {
+        // halving first cannot overflow
+        self.lo / 2.0 + self.hi / 2.0
+    }
+
+    // Rounded up, so midpoint ± radius still contains the interval
+    pub fn radius(self) -> f64 {
+        let mid = self.midpoint();
+        let ((below, below_err), (above, above_err)) =
+            (two_sum(mid, -self.lo), two_sum(self.hi, -mid));
+        up(below, below_err).max(up(above, above_err))
+    }
+
+    pub fn contains(self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    #[inline(always)]
+    pub fn is_point(self) -> bool {
+        self.lo == self.hi
+    }
+
+    pub fn abs(self) -> Interval {
+        if self.lo >= 0.0 {
+            self
+        } else if self.hi <= 0.0 {
+            -self
+        } else {
+            Interval::new(0.0, self.hi.max(-self.lo))
+        }
+    }
+
+    // Applies an increasing function to both bounds
+    fn increasing(self, func: fn(f64) -> f64) -> Interval {
+        Interval::widened(func(self.lo), func(self.hi), LIBM_ULPS)
+    }
+
+    pub fn sqrt(self) -> Interval {
+        if self.lo < 0.0 {
+            return Interval::nan();
+        }
+        // sqrt is correctly rounded, and x - r*r tells which way
+        let sqrt = |x: f64| {
+            let r = x.sqrt();
+            (r, -r.mul_add(r, -x))
+        };
+        let ((lo, lo_err), (hi, hi_err)) = (sqrt(self.lo), sqrt(self.hi));
+        Interval::new(down(lo, lo_err), up(hi, hi_err)).clamp_lo(0.0)
+    }
+
+    pub fn exp(self) -> Interval {
+        self.increasing(f64::exp).clamp_lo(0.0)
+    }
+
+    pub fn ln(self) -> Interval {
+        if self.lo <= 0.0 {
+            return Interval::nan();
+        }
+        self.increasing(f64::ln)
+    }
+
+    pub fn sin(self) -> Interval {
+        // sin peaks at pi/2 + 2k pi and dips at -pi/2 + 2k pi
+        self.periodic(f64::sin, FRAC_PI_2, -FRAC_PI_2)
+    }
+
+    pub fn cos(self) -> Interval {
+        // cos peaks at 2k pi and dips at pi + 2k pi
+        self.periodic(f64::cos, 0.0, PI)
+    }
+
+    fn periodic(self, func: fn(f64) -> f64, peak: f64, dip: f64) -> Interval {
+        if self.is_nan() || self.is_infinite() || self.hi - self.lo >= TAU {
+            return Interval::new(-1.0, 1.0);
+        }
+        let (a, b) = (func(self.lo), func(self.hi));
+        let mut result = Interval::widened(a.min(b), a.max(b), LIBM_ULPS);
+        // widened a little, since the multiples of pi are rounded too
+        let range = Interval::widened(self.lo, self.hi, LIBM_ULPS);
+        if range.contains_periodic(peak) {
+            result.hi = 1.0;
+        }
+        if range.contains_periodic(dip) {
+            result.lo = -1.0;
+        }
+        Interval::new(result.lo.max(-1.0), result.hi.min(1.0))
+    }
+
+    // Whether offset + 2k pi is in the interval for some whole k
+    fn contains_periodic(self, offset: f64) -> bool {
+        let k = ((self.lo - offset) / TAU).ceil();
+        offset + k * TAU <= self.hi
+    }
+
+    fn clamp_lo(self, lo: f64) -> Interval {
+        Interval::new(self.lo.max(lo), self.hi.max(lo))
+    }
+
+    // Raises to a whole power, which is tighter than multiplying x by itself as if they were unrelated
+    fn powi(self, n: i32) -> Interval {
+        if n < 0 {
+            return Interval::point(1.0) / self.powi(-n);
+        }
+        let n = n as u32;
+        if self.lo >= 0.0 {
+            Interval::new(pow_bounds(self.lo, n).0, pow_bounds(self.hi, n).1)
+        } else if self.hi <= 0.0 {
+            let (lo, hi) = (pow_bounds(-self.hi, n).0, pow_bounds(-self.lo, n).1);
+            if n % 2 == 1 {
+                Interval::new(-hi, -lo)
+            } else {
+                Interval::new(lo, hi)
+            }
+        } else if n % 2 == 1 {
+            Interval::new(-pow_bounds(-self.lo, n).1, pow_bounds(self.hi, n).1)
+        } else if n == 0 {
+            Interval::point(1.0)
+        } else {
+            Interval::new(0.0, pow_bounds(self.hi.max(-self.lo), n).1)
+        }
+    }
+
+    // Converts an angle in the angle mode to radians
+    fn to_radians(self, mode: AngleMode) -> Interval {
+        match mode {
+            AngleMode::Radians => self,
+            _ => self * constant(mode.to_radians(1.0)),
+        }
+    }
+}
+
+impl From<f64> for Interval {
+    fn from(x: f64) -> Interval {
+        Interval::point(x)
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, other: Interval) -> Interval {
+        let ((lo, lo_err), (hi, hi_err)) = (two_sum(self.lo, other.lo), two_sum(self.hi, other.hi));
+        Interval::new(down(lo, lo_err), up(hi, hi_err))
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, other: Interval) -> Interval {
+        self + -other
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, other: Interval) -> Interval {
+        self.corners(other, two_product)
+    }
+}
+
+// Dividing by an interval that contains zero has no bounds
+impl Div for Interval {
+    type Output = Interval;
+
+    fn div(self, other: Interval) -> Interval {
+        if other.contains(0.0) {
+            return if other.is_point() {
+                Interval::nan()
+            } else {
+                Interval::ENTIRE
+            };
+        }
+        self.corners(other, two_quotient)
+    }
+}
+
+// Only defined between exact numbers, anything else is NaN
+impl Rem for Interval {
+    type Output = Interval;
+
+    fn rem(self, other: Interval) -> Interval {
+        if self.is_point() && other.is_point() {
+            // % is exact for floats
+            Interval::point(self.lo % other.lo)
+        } else {
+            Interval::nan()
+        }
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Interval {
+        Interval::new(-self.hi, -self.lo)
+    }
+}
+
+impl AddAssign for Interval {
+    fn add_assign(&mut self, other: Interval) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Interval {
+    fn sub_assign(&mut self, other: Interval) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for Interval {
+    fn mul_assign(&mut self, other: Interval) {
+        *self = *self * other;
+    }
+}
+
+impl DivAssign for Interval {
+    fn div_assign(&mut self, other: Interval) {
+        *self = *self / other;
+    }
+}
+
+// An interval is less than another only if all of its values are, overlapping intervals are unordered
+impl PartialOrd for Interval {
+    fn partial_cmp(&self, other: &Interval) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        } else if self.hi < other.lo {
+            Some(Ordering::Less)
+        } else if self.lo > other.hi {
+            Some(Ordering::Greater)
+        } else {
+            None
+        }
+    }
+}
+
+// Parses [lo, hi], x±e and plain numbers
+impl FromStr for Interval {
+    type Err = ParseIntervalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |s: &str| s.trim().parse::<f64>().map_err(|_| ParseIntervalError);
+        if let Some(bounds) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            let (lo, hi) = bounds.split_once(',').ok_or(ParseIntervalError)?;
+            let (lo, hi) = (number(lo)?, number(hi)?);
+            if lo > hi {
+                return Err(ParseIntervalError);
+            }
+            let lo = if parsed_exactly(lo) {
+                lo
+            } else {
+                lo.next_down()
+            };
+            let hi = if parsed_exactly(hi) { hi } else { hi.next_up() };
+            Ok(Interval::new(lo, hi))
+        } else if let Some((mid, radius)) = s.split_once('±') {
+            let (mid, radius) = (number(mid)?, number(radius)?);
+            if radius < 0.0 {
+                return Err(ParseIntervalError);
+            }
+            Ok(Interval::rounded(mid - radius, mid + radius))
+        } else {
+            let x = number(s)?;
+            if parsed_exactly(x) {
+                Ok(Interval::point(x))
+            } else {
+                Ok(Interval::rounded(x, x))
+            }
+        }
+    }
+}
+
+// Decimals like 0.1 and large numbers like 6.022e23 are rounded when parsed,
+// only whole numbers up to 2^53 are surely exact
+fn parsed_exactly(x: f64) -> bool {
+    x.is_infinite() || (x.fract() == 0.0 && x.abs() <= MAX_EXACT)
+}
+
+// Bounds are often tiny, like the ones around 0, so those use exponents
+fn write_bound(f: &mut fmt::Formatter, x: f64) -> fmt::Result {
+    if x != 0.0 && x.is_finite() && (x.abs() < 1e-6 || x.abs() >= 1e16) {
+        write!(f, "{:e}", x)
+    } else {
+        write!(f, "{}", x)
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_point() {
+            return write_bound(f, self.lo);
+        }
+        write!(f, "[")?;
+        write_bound(f, self.lo)?;
+        write!(f, ", ")?;
+        write_bound(f, self.hi)?;
+        write!(f, "]")
+    }
+}
+
+impl Num for Interval {
+    #[inline(always)]
+    fn zero() -> Self {
+        Interval::point(0.0)
+    }
+    #[inline(always)]
+    fn one() -> Self {
+        Interval::point(1.0)
+    }
+    #[inline(always)]
+    fn is_whole(&self) -> bool {
+        self.is_point() && self.lo.fract() == 0.0
+    }
+    fn pow(self, other: Self) -> Self {
+        if other.is_whole() && other.lo.abs() <= i32::MAX as f64 {
+            return self.powi(other.lo as i32);
+        }
+        if self.lo < 0.0 {
+            return Interval::nan();
+        }
+        // x^y grows or shrinks steadily in both x and y when x is positive, so the corners are the bounds
+        let corners = [
+            self.lo.powf(other.lo),
+            self.lo.powf(other.hi),
+            self.hi.powf(other.lo),
+            self.hi.powf(other.hi),
+        ];
+        let lo = corners.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = corners.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Interval::widened(lo, hi, LIBM_ULPS).clamp_lo(0.0)
+    }
+    #[inline(always)]
+    fn is_nan(&self) -> bool {
+        self.lo.is_nan() || self.hi.is_nan()
+    }
+    #[inline(always)]
+    fn is_infinite(&self) -> bool {
+        self.lo.is_infinite() || self.hi.is_infinite()
+    }
+
+    fn try_neg(self) -> Result<Self, NumError> {
+        Ok(-self)
+    }
+    fn try_div(self, other: Self) -> Result<Self, NumError> {
+        if other.contains(0.0) {
+            return Err(NumError::Domain);
+        }
+        Ok(self / other)
+    }
+    fn try_rem(self, other: Self) -> Result<Self, NumError> {
+        if !self.is_point() || !other.is_point() {
+            return Err(NumError::Domain);
+        }
+        Ok(self % other)
+    }
+}
+
+// Registers a function of one interval
+macro_rules! interval_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let result: Interval = $func(args[0]);
+                if result.is_nan() {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                Ok(result)
+            }),
+        )
+    };
+}
+
+// Registers a trigonometric function, converting its argument from the angle mode to radians
+macro_rules! interval_trig_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::AngleFunction(|id, args, mode| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                Ok($func(args[0].to_radians(mode)))
+            }),
+        )
+    };
+}
+
+// A constant that is rounded to f64, so the exact value is within one ulp
+fn constant(x: f64) -> Interval {
+    Interval::rounded(x, x)
+}
+
+impl Default for Interpreter<Interval> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        let vars = &mut interpreter.vars;
+        vars.insert(String::from("pi"), Variant::Num(constant(PI)));
+        vars.insert(
+            String::from("e"),
+            Variant::Num(constant(std::f64::consts::E)),
+        );
+        vars.insert(String::from("tau"), Variant::Num(constant(TAU)));
+        vars.insert(
+            String::from("phi"),
+            Variant::Num(constant(1.618_033_988_749_895_f64)),
+        );
+        vars.insert(
+            String::from("lightspeed"),
+            Variant::Num(Interval::point(299792458.0)),
+        ); // the speed of light in vacuum in m/s
+        vars.insert(
+            String::from("gravconst"),
+            Variant::Num(Interval::rounded(6.67400e-11, 6.67460e-11)), // gravitational constant in m^3/kg/s^2, with its uncertainty
+        );
+
+        // bounds
+        interval_fn!(vars, "lo", |x: Interval| Interval::point(x.lo));
+        interval_fn!(vars, "hi", |x: Interval| Interval::point(x.hi));
+        interval_fn!(vars, "mid", |x: Interval| Interval::point(x.midpoint()));
+        interval_fn!(vars, "radius", |x: Interval| Interval::point(x.radius()));
+
+        interval_fn!(vars, "abs", Interval::abs);
+        interval_fn!(vars, "sqrt", Interval::sqrt);
+        interval_fn!(vars, "exp", Interval::exp);
+        interval_fn!(vars, "ln", Interval::ln);
+        interval_fn!(vars, "log10", |x: Interval| x.ln()
+            / constant(std::f64::consts::LN_10));
+        interval_trig_fn!(vars, "sin", Interval::sin);
+        interval_trig_fn!(vars, "cos", Interval::cos);
+        vars.insert(
+            String::from("deg"),
+            Variant::AngleFunction(|id, args, mode| {
+                // converts degrees to the angle mode, this is what 90° and 90deg mean
+                ensure_arg_count(1, 1, args.len(), id)?;
+                match mode {
+                    AngleMode::Degrees => Ok(args[0]),
+                    _ => Ok(args[0] * constant(mode.from_radians(1f64.to_radians()))),
+                }
+            }),
+        );
+
+        vars.insert(
+            String::from("min"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, usize::MAX, args.len(), id)?;
+                Ok(args
+                    .iter()
+                    .copied()
+                    .reduce(|a, b| Interval::new(a.lo.min(b.lo), a.hi.min(b.hi)))
+                    .unwrap())
+            }),
+        );
+        vars.insert(
+            String::from("max"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, usize::MAX, args.len(), id)?;
+                Ok(args
+                    .iter()
+                    .copied()
+                    .reduce(|a, b| Interval::new(a.lo.max(b.lo), a.hi.max(b.hi)))
+                    .unwrap())
+            }),
+        );
+        register_lazy_builtins(vars);
+        interpreter
+    }
+}
@@ -1,17 +1,57 @@
+#[cfg(feature = "bigint")]
+mod bigint;
+mod compile;
+mod complex;
+#[cfg(feature = "decimal")]
+mod decimal;
+mod derive;
+mod error;
 mod expr;
+mod integer;
 mod interpreter;
+mod interval;
 mod parser;
+mod quantity;
+#[cfg(feature = "rational")]
+mod rational;
+mod render;
+mod simplify;
+mod solver;
 mod tokenizer;
 
+pub use compile::*;
+pub use complex::*;
+pub use derive::*;
+pub use error::*;
 pub use expr::*;
+pub use integer::*;
 pub use interpreter::*;
+pub use interval::*;
 pub use parser::*;
+pub use quantity::*;
+#[cfg(feature = "rational")]
+pub use rational::*;
+pub use render::*;
+pub use simplify::*;
 pub use tokenizer::*;
 
+#[cfg(feature = "bigint")]
+pub use num_bigint::BigInt;
+#[cfg(feature = "decimal")]
+pub use rust_decimal::Decimal;
+
 use std::fmt::Debug;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
 use std::str::FromStr;
 
+// Why a checked operation could not produce a result
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NumError {
+    Overflow,
+    Domain,
+    Dimension, // the units of the operands do not fit together
+}
+
 pub trait Num:
     Debug
     + Clone
@@ -33,6 +73,47 @@ pub trait Num:
     fn one() -> Self;
     fn is_whole(&self) -> bool;
     fn pow(self, other: Self) -> Self;
+    fn is_nan(&self) -> bool {
+        false
+    }
+    fn is_infinite(&self) -> bool {
+        false
+    }
+
+    // Used by the interpreter, for types whose operators can overflow or panic
+    fn try_neg(self) -> Result<Self, NumError> {
+        Self::zero().try_sub(self)
+    }
+    fn try_add(self, other: Self) -> Result<Self, NumError> {
+        Ok(self + other)
+    }
+    fn try_sub(self, other: Self) -> Result<Self, NumError> {
+        Ok(self - other)
+    }
+    fn try_mul(self, other: Self) -> Result<Self, NumError> {
+        Ok(self * other)
+    }
+    fn try_div(self, other: Self) -> Result<Self, NumError> {
+        Ok(self / other)
+    }
+    fn try_rem(self, other: Self) -> Result<Self, NumError> {
+        Ok(self % other)
+    }
+    fn try_pow(self, other: Self) -> Result<Self, NumError> {
+        Ok(self.pow(other))
+    }
+    // Whether `in` and `to` convert between units, otherwise they are an error after a value
+    fn has_units() -> bool {
+        false
+    }
+    // `10 km in mi`, for types with units. Otherwise it is how many of `unit` fit in self.
+    fn try_convert(self, unit: Self) -> Result<Self, NumError> {
+        self.try_div(unit)
+    }
+    // Rounds a guess of the equation solver, for exact types whose guesses would keep growing
+    fn approximate(self) -> Self {
+        self
+    }
 }
 
 // Default impls for Num
@@ -53,6 +134,18 @@ impl Num for f32 {
     fn pow(self, other: Self) -> Self {
         self.powf(other)
     }
+    #[inline(always)]
+    fn is_nan(&self) -> bool {
+        f32::is_nan(*self)
+    }
+    #[inline(always)]
+    fn is_infinite(&self) -> bool {
+        f32::is_infinite(*self)
+    }
+    #[inline(always)]
+    fn try_neg(self) -> Result<Self, NumError> {
+        Ok(-self)
+    }
 }
 
 impl Num for f64 {
@@ -72,4 +165,16 @@ impl Num for f64 {
     fn pow(self, other: Self) -> Self {
         self.powf(other)
     }
+    #[inline(always)]
+    fn is_nan(&self) -> bool {
+        f64::is_nan(*self)
+    }
+    #[inline(always)]
+    fn is_infinite(&self) -> bool {
+        f64::is_infinite(*self)
+    }
+    #[inline(always)]
+    fn try_neg(self) -> Result<Self, NumError> {
+        Ok(-self)
+    }
 }
@@ -7,6 +7,8 @@ use std::slice::Iter;
 pub enum ParseErrorCode<'t, N: Num> {
     ExpectedValue,
     ExpectedClosingParen,
+    ExpectedColon,
+    ExpectedArgs(&'t str, usize), // Name of the function and how many it takes
     UnexpectedToken(&'t Token<'t, N>),
     UnexpectedEOF,
 }
@@ -18,7 +20,7 @@ pub struct ParseError<'t, N: Num> {
     pub span: Range<usize>,
 }
 
-pub type ParseResult<'input, N> = Result<Expr<'input, N>, ParseError<'input, N>>;
+pub type ParseResult<'input, N> = Result<Expr<N>, ParseError<'input, N>>;
 
 macro_rules! error {
     ($code:expr, $span:expr) => {
@@ -53,12 +55,122 @@ fn parse_expr<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
 }
 
 fn parse_eq<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
-    let mut result = parse_add(tokens)?;
+    let mut result = parse_cond(tokens)?;
     while let Some(peek_tok) = tokens.peek() {
         if peek_tok.value == TokenValue::Op(OpVal::Eq) {
+            let span = peek_tok.span.clone();
             tokens.next(); // Consume '='
+            let rhs = parse_cond(tokens)?;
+            result = match result {
+                // f(x, y) = ... defines a function when every argument is a plain variable,
+                // unless f is a builtin, then the interpreter solves it like an equation
+                Expr::FuncOrVarMul(id, args, _)
+                    if args.iter().all(|arg| matches!(arg, Expr::Var(..))) =>
+                {
+                    let params = args
+                        .into_iter()
+                        .map(|arg| match arg {
+                            Expr::Var(param, _) => param,
+                            _ => unreachable!(),
+                        })
+                        .collect();
+                    Expr::FuncDef(id, params, Box::new(rhs), span)
+                }
+                result => Expr::Eq(Box::new(result), Box::new(rhs), span),
+            };
+        } else {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+// '?' is right-associative, so a ? b : c ? d : e is a ? b : (c ? d : e)
+fn parse_cond<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
+    let cond = parse_or(tokens)?;
+    if let Some(peek_tok) = tokens.peek() {
+        if peek_tok.value == TokenValue::Symbol(SymbolVal::Question) {
+            tokens.next(); // Consume '?'
+            let then = parse_expr(tokens)?;
+            match tokens.next() {
+                Some(tok) if tok.value == TokenValue::Symbol(SymbolVal::Colon) => {}
+                Some(tok) => return Err(error!(ExpectedColon, tok.span.clone())),
+                None => return Err(error!(UnexpectedEOF, 0..0)),
+            }
+            let otherwise = parse_cond(tokens)?;
+            return Ok(Expr::If(
+                Box::new(cond),
+                Box::new(then),
+                Box::new(otherwise),
+            ));
+        }
+    }
+    Ok(cond)
+}
+
+fn parse_or<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
+    let mut result = parse_and(tokens)?;
+    while let Some(peek_tok) = tokens.peek() {
+        if peek_tok.value == TokenValue::Op(OpVal::Or) {
+            let span = peek_tok.span.clone();
+            tokens.next(); // Consume '||'
+            let rhs = parse_and(tokens)?;
+            result = Expr::Op(OpVal::Or, Box::new(result), Box::new(rhs), span);
+        } else {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+fn parse_and<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
+    let mut result = parse_compare(tokens)?;
+    while let Some(peek_tok) = tokens.peek() {
+        if peek_tok.value == TokenValue::Op(OpVal::And) {
+            let span = peek_tok.span.clone();
+            tokens.next(); // Consume '&&'
+            let rhs = parse_compare(tokens)?;
+            result = Expr::Op(OpVal::And, Box::new(result), Box::new(rhs), span);
+        } else {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+// All comparisons share one left-associative level, so a < b == c < d is ((a < b) == c) < d
+fn parse_compare<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
+    let mut result = parse_convert(tokens)?;
+    while let Some(peek_tok) = tokens.peek() {
+        match peek_tok.value {
+            TokenValue::Op(
+                op @ (OpVal::Less
+                | OpVal::LessEq
+                | OpVal::Greater
+                | OpVal::GreaterEq
+                | OpVal::Equal
+                | OpVal::NotEqual),
+            ) => {
+                let span = peek_tok.span.clone();
+                tokens.next(); // Consume the comparison
+                let rhs = parse_convert(tokens)?;
+                result = Expr::Op(op, Box::new(result), Box::new(rhs), span);
+            }
+            _ => break,
+        }
+    }
+    Ok(result)
+}
+
+// 'in' binds looser than '+', so 1 km + 1 m in ft converts the sum
+fn parse_convert<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
+    let mut result = parse_add(tokens)?;
+    while let Some(peek_tok) = tokens.peek() {
+        if peek_tok.value == TokenValue::Op(OpVal::In) {
+            let span = peek_tok.span.clone();
+            tokens.next(); // Consume 'in' or 'to'
             let rhs = parse_add(tokens)?;
-            result = Expr::Eq(Box::new(result), Box::new(rhs));
+            result = Expr::Op(OpVal::In, Box::new(result), Box::new(rhs), span);
         } else {
             break;
         }
@@ -71,9 +183,10 @@ fn parse_add<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
     while let Some(peek_tok) = tokens.peek() {
         match peek_tok.value {
             TokenValue::Op(op) if op == OpVal::Add || op == OpVal::Sub => {
+                let span = peek_tok.span.clone();
                 tokens.next(); // Consume '+' or '-'
                 let rhs = parse_mul(tokens)?;
-                result = Expr::Op(op, Box::new(result), Box::new(rhs));
+                result = Expr::Op(op, Box::new(result), Box::new(rhs), span);
             }
             _ => break,
         }
@@ -82,13 +195,14 @@ fn parse_add<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
 }
 
 fn parse_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
-    let mut result = parse_pow(tokens)?;
+    let mut result = parse_unary(tokens)?;
     while let Some(peek_tok) = tokens.peek() {
         match peek_tok.value {
             TokenValue::Op(op) if op == OpVal::Mul || op == OpVal::Div || op == OpVal::Mod => {
+                let span = peek_tok.span.clone();
                 tokens.next(); // Consume '*' or '/' or '%'
-                let rhs = parse_pow(tokens)?;
-                result = Expr::Op(op, Box::new(result), Box::new(rhs));
+                let rhs = parse_unary(tokens)?;
+                result = Expr::Op(op, Box::new(result), Box::new(rhs), span);
             }
             _ => break,
         }
@@ -96,15 +210,27 @@ fn parse_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
     Ok(result)
 }
 
+// Unary minus binds tighter than '*' but looser than '^', so -x^2 is -(x^2)
+fn parse_unary<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
+    if let Some(peek_tok) = tokens.peek() {
+        if peek_tok.value == TokenValue::Op(OpVal::Sub) {
+            let span = peek_tok.span.clone();
+            tokens.next(); // Consume '-'
+            return Ok(Expr::Neg(Box::new(parse_unary(tokens)?), span));
+        }
+    }
+    parse_pow(tokens)
+}
+
+// '^' is right-associative, so 2^3^2 is 2^(3^2)
 fn parse_pow<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
-    let mut result = parse_parentheses_mul(tokens)?;
-    while let Some(peek_tok) = tokens.peek() {
+    let result = parse_parentheses_mul(tokens)?;
+    if let Some(peek_tok) = tokens.peek() {
         if peek_tok.value == TokenValue::Op(OpVal::Pow) {
+            let span = peek_tok.span.clone();
             tokens.next(); // Consume '^'
-            let rhs = parse_factor(tokens)?;
-            result = Expr::Op(OpVal::Pow, Box::new(result), Box::new(rhs));
-        } else {
-            break;
+            let rhs = parse_unary(tokens)?;
+            return Ok(Expr::Op(OpVal::Pow, Box::new(result), Box::new(rhs), span));
         }
     }
     Ok(result)
@@ -115,13 +241,28 @@ fn parse_parentheses_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResu
         Ok(func_or_var_mul?)
     } else {
         let mut result = parse_factorial(tokens)?;
+        // A number directly followed by an identifier is multiplied by it, and binds looser than '^',
+        // so 2x^2 is 2 * x^2. Errors of the implicit '*' point at the identifier.
+        if let (
+            Expr::Num(_),
+            Some(Token {
+                value: TokenValue::Id(_),
+                span,
+            }),
+        ) = (&result, tokens.peek())
+        {
+            let span = span.clone();
+            let rhs = parse_pow(tokens)?;
+            return Ok(Expr::Op(OpVal::Mul, Box::new(result), Box::new(rhs), span));
+        }
         while let Some(peek_tok) = tokens.peek() {
             if peek_tok.value == TokenValue::Symbol(SymbolVal::LP) {
+                let span = peek_tok.span.clone();
                 tokens.next(); // Consume '('
                 let rhs = parse_expr(tokens)?;
                 if let Some(tok) = tokens.next() {
                     if tok.value == TokenValue::Symbol(SymbolVal::RP) {
-                        result = Expr::Op(OpVal::Mul, Box::new(result), Box::new(rhs));
+                        result = Expr::Op(OpVal::Mul, Box::new(result), Box::new(rhs), span);
                     } else {
                         return Err(error!(ExpectedClosingParen, tok.span.clone()));
                     }
@@ -143,8 +284,9 @@ fn parse_func_or_var_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> Option<Pa
     match tokens.peek() {
         Some(Token {
             value: TokenValue::Id(id),
-            ..
+            span,
         }) => {
+            let start = span.start;
             // Check for opening parentheses
             if let Some(tok) = tokens.peek_nth(1) {
                 if tok.value != TokenValue::Symbol(SymbolVal::LP) {
@@ -163,13 +305,15 @@ fn parse_func_or_var_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> Option<Pa
             // Shortcut: function has no parameters
             if let Some(tok) = tokens.peek() {
                 if tok.value == TokenValue::Symbol(SymbolVal::RP) {
+                    let end = tok.span.end;
                     tokens.next(); // Consume ')'
-                    return Some(Ok(Expr::FuncOrVarMul(id, Vec::new())));
+                    return Some(call(id, Vec::new(), start..end));
                 }
             }
 
             // Collecting function parameters
             let mut params = Vec::with_capacity(3);
+            let mut end = start + id.len();
             while let Ok(expr) = parse_expr(tokens) {
                 params.push(expr);
                 match tokens.next() {
@@ -181,26 +325,46 @@ fn parse_func_or_var_mul<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> Option<Pa
                     }
                     Some(Token {
                         value: TokenValue::Symbol(SymbolVal::RP),
-                        ..
+                        span,
                     }) => {
+                        end = span.end;
                         break;
                     }
                     Some(tok) => return Some(Err(error!(UnexpectedToken(tok), tok.span.clone()))),
                     None => return Some(Err(error!(UnexpectedEOF, 0..0))),
                 }
             }
-            Some(Ok(Expr::FuncOrVarMul(id, params)))
+            Some(call(id, params, start..end))
         }
         _ => None,
     }
 }
 
+// if(c, a, b) is the same as c ? a : b, so it is parsed into one
+fn call<'t, N: Num>(id: &'t str, params: Vec<Expr<N>>, span: Range<usize>) -> ParseResult<'t, N> {
+    if id != "if" {
+        return Ok(Expr::FuncOrVarMul(id.to_string(), params, span));
+    }
+    match <[Expr<N>; 3]>::try_from(params) {
+        Ok([cond, then, otherwise]) => Ok(Expr::If(
+            Box::new(cond),
+            Box::new(then),
+            Box::new(otherwise),
+        )),
+        Err(_) => Err(error!(ExpectedArgs(id, 3), span)),
+    }
+}
+
 fn parse_factorial<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
     let mut result = parse_factor(tokens)?;
     while let Some(peek_tok) = tokens.peek() {
+        let span = peek_tok.span.clone();
         if peek_tok.value == TokenValue::Op(OpVal::Exclaim) {
             tokens.next(); // Consume '!'
-            result = Expr::FuncOrVarMul("factorial", vec![result]);
+            result = Expr::FuncOrVarMul(String::from("factorial"), vec![result], span);
+        } else if peek_tok.value == TokenValue::Symbol(SymbolVal::Degree) {
+            tokens.next(); // Consume '°'
+            result = Expr::FuncOrVarMul(String::from("deg"), vec![result], span);
         } else {
             break;
         }
@@ -211,12 +375,9 @@ fn parse_factorial<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t,
 fn parse_factor<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N> {
     match tokens.next() {
         Some(tok) => match &tok.value {
-            TokenValue::Num(num) => Ok(Expr::Num(num)),
-            TokenValue::Id(id) => Ok(Expr::Var(id)),
-            TokenValue::Op(op) => match op {
-                OpVal::Sub => Ok(Expr::Neg(Box::new(parse_expr(tokens)?))),
-                _ => Err(error!(UnexpectedToken(tok), tok.span.clone())),
-            },
+            TokenValue::Num(num) => Ok(Expr::Num(num.clone())),
+            TokenValue::Id(id) => Ok(Expr::Var(id.to_string(), tok.span.clone())),
+            TokenValue::Op(_) => Err(error!(UnexpectedToken(tok), tok.span.clone())),
             TokenValue::Symbol(sym) => match sym {
                 SymbolVal::LP => {
                     let expr = parse_expr(tokens)?;
@@ -234,11 +395,12 @@ fn parse_factor<'t, N: Num>(tokens: &mut TokenIter<'t, N>) -> ParseResult<'t, N>
                 SymbolVal::Pipe => {
                     let expr = parse_expr(tokens)?;
                     // Expect a closing pipe
-                    if let Some(tok) = tokens.next() {
-                        if tok.value == TokenValue::Symbol(SymbolVal::Pipe) {
-                            Ok(Expr::FuncOrVarMul("abs", vec![expr]))
+                    if let Some(end) = tokens.next() {
+                        if end.value == TokenValue::Symbol(SymbolVal::Pipe) {
+                            let span = tok.span.start..end.span.end;
+                            Ok(Expr::FuncOrVarMul(String::from("abs"), vec![expr], span))
                         } else {
-                            Err(error!(UnexpectedToken(tok), tok.span.clone()))
+                            Err(error!(UnexpectedToken(end), end.span.clone()))
                         }
                     } else {
                         Err(error!(UnexpectedEOF, 0..0))
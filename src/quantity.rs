@@ -0,0 +1,694 @@
+use crate::{
+    ensure_arg_count, register_lazy_builtins, InterpretError, Interpreter, Num, NumError, Variant,
+};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+use std::sync::Arc;
+
+// Symbols of the SI base units, in the order they are printed
+const BASE_UNITS: [&str; 7] = ["kg", "m", "s", "A", "K", "mol", "cd"];
+
+// Powers of the SI base units kg, m, s, A, K, mol and cd
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Dimension(pub [i32; 7]);
+
+impl Dimension {
+    pub const NONE: Dimension = Dimension([0, 0, 0, 0, 0, 0, 0]);
+    pub const MASS: Dimension = Dimension([1, 0, 0, 0, 0, 0, 0]);
+    pub const LENGTH: Dimension = Dimension([0, 1, 0, 0, 0, 0, 0]);
+    pub const TIME: Dimension = Dimension([0, 0, 1, 0, 0, 0, 0]);
+    pub const CURRENT: Dimension = Dimension([0, 0, 0, 1, 0, 0, 0]);
+    pub const TEMPERATURE: Dimension = Dimension([0, 0, 0, 0, 1, 0, 0]);
+    pub const AMOUNT: Dimension = Dimension([0, 0, 0, 0, 0, 1, 0]);
+    pub const LUMINOSITY: Dimension = Dimension([0, 0, 0, 0, 0, 0, 1]);
+
+    #[inline(always)]
+    pub fn is_none(self) -> bool {
+        self == Dimension::NONE
+    }
+
+    // This panics if a power overflows, the interpreter uses checked_powi
+    pub fn powi(self, n: i32) -> Dimension {
+        self.checked_powi(n).expect("unit power overflowed")
+    }
+
+    pub fn checked_powi(self, n: i32) -> Option<Dimension> {
+        let mut result = Dimension::NONE;
+        for (power, base) in result.0.iter_mut().zip(self.0) {
+            *power = base.checked_mul(n)?;
+        }
+        Some(result)
+    }
+
+    // multiplying units adds their powers
+    pub fn checked_mul(self, other: Dimension) -> Option<Dimension> {
+        let mut result = self;
+        for (power, other) in result.0.iter_mut().zip(other.0) {
+            *power = power.checked_add(other)?;
+        }
+        Some(result)
+    }
+
+    pub fn checked_div(self, other: Dimension) -> Option<Dimension> {
+        let mut result = self;
+        for (power, other) in result.0.iter_mut().zip(other.0) {
+            *power = power.checked_sub(other)?;
+        }
+        Some(result)
+    }
+
+    // None if a power would not be whole, like the square root of m
+    fn powf(self, n: f64) -> Option<Dimension> {
+        let mut result = Dimension::NONE;
+        for (power, base) in result.0.iter_mut().zip(self.0) {
+            let scaled = base as f64 * n;
+            if scaled.fract() != 0.0 || scaled.abs() > i32::MAX as f64 {
+                return None;
+            }
+            *power = scaled as i32;
+        }
+        Some(result)
+    }
+}
+
+// These panic if a power overflows, quantities use checked_mul and checked_div
+impl Mul for Dimension {
+    type Output = Dimension;
+
+    fn mul(self, other: Dimension) -> Dimension {
+        self.checked_mul(other).expect("unit power overflowed")
+    }
+}
+
+impl Div for Dimension {
+    type Output = Dimension;
+
+    fn div(self, other: Dimension) -> Dimension {
+        self.checked_div(other).expect("unit power overflowed")
+    }
+}
+
+// Prints like kg*m^2/s^2, or s^-1 if nothing is above the line
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let units = |above: bool| {
+            BASE_UNITS
+                .iter()
+                .zip(self.0)
+                .filter(|(_, power)| *power != 0 && (*power > 0) == above)
+                .map(|(unit, power)| match power.abs() {
+                    1 => unit.to_string(),
+                    power => format!("{}^{}", unit, power),
+                })
+                .collect::<Vec<_>>()
+        };
+        let (above, below) = (units(true), units(false));
+        if above.is_empty() {
+            let below = BASE_UNITS
+                .iter()
+                .zip(self.0)
+                .filter(|(_, power)| *power != 0)
+                .map(|(unit, power)| format!("{}^{}", unit, power))
+                .collect::<Vec<_>>();
+            return write!(f, "{}", below.join("*"));
+        }
+        write!(f, "{}", above.join("*"))?;
+        for unit in below {
+            write!(f, "/{}", unit)?;
+        }
+        Ok(())
+    }
+}
+
+// A named unit that a quantity is printed in, such as km/h
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub name: Arc<str>,
+    pub scale: f64, // the size of the unit in SI base units
+}
+
+// Joins unit names with an operator, adding parentheses where they are needed to keep the meaning
+fn join(lhs: &str, op: char, rhs: &str) -> Arc<str> {
+    let wrap = |name: &str, ops: &[char]| {
+        if name.contains(ops) {
+            format!("({})", name)
+        } else {
+            name.to_string()
+        }
+    };
+    match op {
+        '*' => match (power_of(lhs), power_of(rhs)) {
+            (Some((a, n)), Some((b, m))) if a == b && n + m != 1 => format!("{}^{}", a, n + m),
+            (Some((a, n)), Some((b, m))) if a == b && n + m == 1 => a.to_string(),
+            _ => format!("{}*{}", lhs, rhs),
+        },
+        '/' => format!("{}/{}", lhs, wrap(rhs, &['*', '/'])),
+        '^' => format!("{}^{}", wrap(lhs, &['*', '/', '^']), rhs),
+        _ => format!("{}{}{}", lhs, op, rhs),
+    }
+    .into()
+}
+
+// Splits a single unit name like km or km^2 into its name and whole power, so km*km can be km^2
+fn power_of(name: &str) -> Option<(&str, i64)> {
+    let (base, power) = match name.split_once('^') {
+        Some((base, power)) => (base, power.parse().ok()?),
+        None => (name, 1),
+    };
+    if base.is_empty() || base.contains(['*', '/', '^', '(', ')']) {
+        return None;
+    }
+    Some((base, power))
+}
+
+// A number with a physical dimension.
+// The value is in `unit` if there is one, so 3 km/h stays 3 km/h, otherwise it is in SI base units.
+#[derive(Debug, Clone)]
+pub struct Quantity {
+    pub value: f64,
+    pub dim: Dimension,
+    pub unit: Option<Unit>,
+}
+
+impl Quantity {
+    #[inline(always)]
+    pub fn new(value: f64, dim: Dimension) -> Quantity {
+        Quantity {
+            value,
+            dim,
+            unit: None,
+        }
+    }
+
+    // One of a named unit that is `scale` SI base units large
+    pub fn unit(name: &str, scale: f64, dim: Dimension) -> Quantity {
+        Quantity {
+            value: 1.0,
+            dim,
+            unit: Some(Unit {
+                name: name.into(),
+                scale,
+            }),
+        }
+    }
+
+    fn nan() -> Quantity {
+        Quantity::from(f64::NAN)
+    }
+
+    // The value in SI base units
+    pub fn si_value(&self) -> f64 {
+        match &self.unit {
+            Some(unit) => self.value * unit.scale,
+            None => self.value,
+        }
+    }
+
+    pub fn to_si(&self) -> Quantity {
+        Quantity::new(self.si_value(), self.dim)
+    }
+
+    // Plain numbers keep the unit of what they are multiplied with
+    #[inline(always)]
+    pub fn is_plain(&self) -> bool {
+        self.dim.is_none() && self.unit.is_none()
+    }
+
+    fn with_value(&self, value: f64) -> Quantity {
+        Quantity {
+            value,
+            dim: self.dim,
+            unit: self.unit.clone(),
+        }
+    }
+
+    pub fn abs(&self) -> Quantity {
+        self.with_value(self.value.abs())
+    }
+
+    // Expresses the quantity in the unit of `unit`, which has to be exactly one of it
+    pub fn convert(&self, unit: &Quantity) -> Result<Quantity, NumError> {
+        if self.dim != unit.dim {
+            return Err(NumError::Dimension);
+        }
+        if unit.value != 1.0 {
+            return Err(NumError::Domain);
+        }
+        Ok(Quantity {
+            value: self.si_value() / unit.si_value(),
+            dim: self.dim,
+            unit: unit.unit.clone(),
+        })
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(value: f64) -> Quantity {
+        Quantity::new(value, Dimension::NONE)
+    }
+}
+
+impl Add for Quantity {
+    type Output = Quantity;
+
+    fn add(self, other: Quantity) -> Quantity {
+        self.try_add(other).unwrap_or_else(|_| Quantity::nan())
+    }
+}
+
+impl Sub for Quantity {
+    type Output = Quantity;
+
+    fn sub(self, other: Quantity) -> Quantity {
+        self + -other
+    }
+}
+
+impl Mul for Quantity {
+    type Output = Quantity;
+
+    fn mul(self, other: Quantity) -> Quantity {
+        self.try_mul(other).unwrap_or_else(|_| Quantity::nan())
+    }
+}
+
+impl Div for Quantity {
+    type Output = Quantity;
+
+    fn div(self, other: Quantity) -> Quantity {
+        self.try_div(other).unwrap_or_else(|_| Quantity::nan())
+    }
+}
+
+// Only defined between quantities of the same dimension, anything else is NaN
+impl Rem for Quantity {
+    type Output = Quantity;
+
+    fn rem(self, other: Quantity) -> Quantity {
+        self.try_rem(other).unwrap_or_else(|_| Quantity::nan())
+    }
+}
+
+impl Neg for Quantity {
+    type Output = Quantity;
+
+    fn neg(self) -> Quantity {
+        self.with_value(-self.value)
+    }
+}
+
+impl AddAssign for Quantity {
+    fn add_assign(&mut self, other: Quantity) {
+        *self = self.clone() + other;
+    }
+}
+
+impl SubAssign for Quantity {
+    fn sub_assign(&mut self, other: Quantity) {
+        *self = self.clone() - other;
+    }
+}
+
+impl MulAssign for Quantity {
+    fn mul_assign(&mut self, other: Quantity) {
+        *self = self.clone() * other;
+    }
+}
+
+impl DivAssign for Quantity {
+    fn div_assign(&mut self, other: Quantity) {
+        *self = self.clone() / other;
+    }
+}
+
+// Quantities are equal when they are the same amount, so 1 km is 1000 m
+impl PartialEq for Quantity {
+    fn eq(&self, other: &Quantity) -> bool {
+        self.dim == other.dim && self.si_value() == other.si_value()
+    }
+}
+
+// Only quantities of the same dimension are ordered
+impl PartialOrd for Quantity {
+    fn partial_cmp(&self, other: &Quantity) -> Option<Ordering> {
+        if self.dim == other.dim {
+            self.si_value().partial_cmp(&other.si_value())
+        } else {
+            None
+        }
+    }
+}
+
+// Parses plain numbers, units come from multiplying by unit variables like m
+impl FromStr for Quantity {
+    type Err = std::num::ParseFloatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Quantity::from(s.parse::<f64>()?))
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.unit {
+            Some(unit) => write!(f, "{} {}", self.value, unit.name),
+            None if self.dim.is_none() => write!(f, "{}", self.value),
+            None => write!(f, "{} {}", self.value, self.dim),
+        }
+    }
+}
+
+impl Num for Quantity {
+    #[inline(always)]
+    fn zero() -> Self {
+        Quantity::from(0.0)
+    }
+    #[inline(always)]
+    fn one() -> Self {
+        Quantity::from(1.0)
+    }
+    #[inline(always)]
+    fn is_whole(&self) -> bool {
+        self.dim.is_none() && self.si_value().fract() == 0.0
+    }
+    fn pow(self, other: Self) -> Self {
+        self.try_pow(other).unwrap_or_else(|_| Quantity::nan())
+    }
+    #[inline(always)]
+    fn is_nan(&self) -> bool {
+        self.value.is_nan()
+    }
+    #[inline(always)]
+    fn is_infinite(&self) -> bool {
+        self.value.is_infinite()
+    }
+
+    fn try_neg(self) -> Result<Self, NumError> {
+        Ok(-self)
+    }
+    fn try_add(self, other: Self) -> Result<Self, NumError> {
+        if self.dim != other.dim {
+            // a plain 0 fits anything, like the start of a sum
+            return match (self.is_plain(), other.is_plain()) {
+                (true, _) if self.value == 0.0 => Ok(other),
+                (_, true) if other.value == 0.0 => Ok(self),
+                _ => Err(NumError::Dimension),
+            };
+        }
+        if self.unit == other.unit {
+            Ok(self.with_value(self.value + other.value))
+        } else {
+            Ok(Quantity::new(self.si_value() + other.si_value(), self.dim))
+        }
+    }
+    fn try_sub(self, other: Self) -> Result<Self, NumError> {
+        self.try_add(-other)
+    }
+    fn try_mul(self, other: Self) -> Result<Self, NumError> {
+        let dim = self.dim.checked_mul(other.dim).ok_or(NumError::Overflow)?;
+        Ok(match (&self.unit, &other.unit) {
+            _ if other.is_plain() => self.with_value(self.value * other.value),
+            _ if self.is_plain() => other.with_value(self.value * other.value),
+            // units that cancel out, like km/m, leave a plain number
+            (Some(a), Some(b)) if !dim.is_none() => Quantity {
+                value: self.value * other.value,
+                dim,
+                unit: Some(Unit {
+                    name: join(&a.name, '*', &b.name),
+                    scale: a.scale * b.scale,
+                }),
+            },
+            _ => Quantity::new(self.si_value() * other.si_value(), dim),
+        })
+    }
+    fn try_div(self, other: Self) -> Result<Self, NumError> {
+        let dim = self.dim.checked_div(other.dim).ok_or(NumError::Overflow)?;
+        Ok(match (&self.unit, &other.unit) {
+            _ if other.is_plain() => self.with_value(self.value / other.value),
+            (Some(a), Some(b)) if !dim.is_none() => Quantity {
+                value: self.value / other.value,
+                dim,
+                unit: Some(Unit {
+                    name: join(&a.name, '/', &b.name),
+                    scale: a.scale / b.scale,
+                }),
+            },
+            _ => Quantity::new(self.si_value() / other.si_value(), dim),
+        })
+    }
+    fn try_rem(self, other: Self) -> Result<Self, NumError> {
+        if self.dim != other.dim {
+            return Err(NumError::Dimension);
+        }
+        if self.unit == other.unit {
+            Ok(self.with_value(self.value % other.value))
+        } else {
+            Ok(Quantity::new(self.si_value() % other.si_value(), self.dim))
+        }
+    }
+    fn try_pow(self, other: Self) -> Result<Self, NumError> {
+        if !other.dim.is_none() {
+            return Err(NumError::Dimension);
+        }
+        let n = other.si_value();
+        let dim = self.dim.powf(n).ok_or(NumError::Dimension)?;
+        match &self.unit {
+            Some(unit) if n.fract() == 0.0 && !dim.is_none() => Ok(Quantity {
+                value: self.value.powf(n),
+                dim,
+                unit: Some(Unit {
+                    name: join(&unit.name, '^', &n.to_string()),
+                    scale: unit.scale.powf(n),
+                }),
+            }),
+            _ => Ok(Quantity::new(self.si_value().powf(n), dim)),
+        }
+    }
+    fn has_units() -> bool {
+        true
+    }
+    fn try_convert(self, unit: Self) -> Result<Self, NumError> {
+        self.convert(&unit)
+    }
+}
+
+// Registers a function of one plain number, such as ln
+macro_rules! dimensionless_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                if !args[0].dim.is_none() {
+                    return Err(InterpretError::DimensionMismatch(id.into()));
+                }
+                let result: f64 = $func(args[0].si_value());
+                if result.is_nan() {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                Ok(Quantity::from(result))
+            }),
+        )
+    };
+}
+
+// Registers a trigonometric function of a plain angle, converting it from the angle mode to radians
+macro_rules! dimensionless_trig_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::AngleFunction(|id, args, mode| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                if !args[0].dim.is_none() {
+                    return Err(InterpretError::DimensionMismatch(id.into()));
+                }
+                Ok(Quantity::from($func(mode.to_radians(args[0].si_value()))))
+            }),
+        )
+    };
+}
+
+impl Default for Interpreter<Quantity> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        let vars = &mut interpreter.vars;
+        let (kg, m, s) = (Dimension::MASS, Dimension::LENGTH, Dimension::TIME);
+        let (a, k, mol, cd) = (
+            Dimension::CURRENT,
+            Dimension::TEMPERATURE,
+            Dimension::AMOUNT,
+            Dimension::LUMINOSITY,
+        );
+        let (newton, joule) = (kg * m / s.powi(2), kg * m.powi(2) / s.powi(2));
+        let (watt, volt) = (joule / s, joule / s / a);
+
+        vars.insert(
+            String::from("pi"),
+            Variant::Num(Quantity::from(std::f64::consts::PI)),
+        );
+        vars.insert(
+            String::from("e"),
+            Variant::Num(Quantity::from(std::f64::consts::E)),
+        );
+        vars.insert(
+            String::from("tau"),
+            Variant::Num(Quantity::from(std::f64::consts::TAU)),
+        );
+        vars.insert(
+            String::from("phi"),
+            Variant::Num(Quantity::from(1.618_033_988_749_895_f64)),
+        );
+        vars.insert(
+            String::from("lightspeed"),
+            Variant::Num(Quantity::new(299792458.0, m / s)),
+        ); // the speed of light in vacuum
+        vars.insert(
+            String::from("planck"),
+            Variant::Num(Quantity::new(6.62607015e-34, joule * s)),
+        );
+        vars.insert(
+            String::from("planckbar"),
+            Variant::Num(Quantity::new(1.054571817e-34, joule * s)),
+        );
+        vars.insert(
+            String::from("gravconst"),
+            Variant::Num(Quantity::new(6.67430e-11, m.powi(3) / kg / s.powi(2))),
+        );
+
+        // name, size in SI base units, dimension
+        let units = [
+            // length, inches are inch since in converts
+            ("m", 1.0, m),
+            ("km", 1e3, m),
+            ("cm", 1e-2, m),
+            ("mm", 1e-3, m),
+            ("um", 1e-6, m),
+            ("nm", 1e-9, m),
+            ("inch", 0.0254, m),
+            ("ft", 0.3048, m),
+            ("yd", 0.9144, m),
+            ("mi", 1609.344, m),
+            ("nmi", 1852.0, m),
+            ("au", 149_597_870_700.0, m),
+            ("ly", 9_460_730_472_580_800.0, m),
+            // mass
+            ("kg", 1.0, kg),
+            ("g", 1e-3, kg),
+            ("mg", 1e-6, kg),
+            ("t", 1e3, kg),
+            ("lb", 0.453_592_37, kg),
+            ("oz", 0.028_349_523_125, kg),
+            // time
+            ("s", 1.0, s),
+            ("ms", 1e-3, s),
+            ("us", 1e-6, s),
+            ("ns", 1e-9, s),
+            ("min", 60.0, s),
+            ("h", 3600.0, s),
+            ("day", 86400.0, s),
+            ("week", 604_800.0, s),
+            ("yr", 31_557_600.0, s), // a julian year
+            // the other base units
+            ("A", 1.0, a),
+            ("mA", 1e-3, a),
+            ("K", 1.0, k),
+            ("mol", 1.0, mol),
+            ("cd", 1.0, cd),
+            // area and volume
+            ("ha", 1e4, m.powi(2)),
+            ("L", 1e-3, m.powi(3)),
+            ("mL", 1e-6, m.powi(3)),
+            ("gal", 3.785_411_784e-3, m.powi(3)),
+            // speed and frequency
+            ("mph", 0.44704, m / s),
+            ("kn", 1852.0 / 3600.0, m / s),
+            ("Hz", 1.0, s.powi(-1)),
+            ("kHz", 1e3, s.powi(-1)),
+            ("MHz", 1e6, s.powi(-1)),
+            ("GHz", 1e9, s.powi(-1)),
+            // force and pressure
+            ("N", 1.0, newton),
+            ("kN", 1e3, newton),
+            ("lbf", 4.448_221_615_260_5, newton),
+            ("Pa", 1.0, newton / m.powi(2)),
+            ("kPa", 1e3, newton / m.powi(2)),
+            ("bar", 1e5, newton / m.powi(2)),
+            ("atm", 101_325.0, newton / m.powi(2)),
+            ("psi", 6_894.757_293_168_361, newton / m.powi(2)),
+            // energy and power
+            ("J", 1.0, joule),
+            ("kJ", 1e3, joule),
+            ("cal", 4.184, joule),
+            ("kcal", 4184.0, joule),
+            ("Wh", 3600.0, joule),
+            ("kWh", 3.6e6, joule),
+            ("eV", 1.602_176_634e-19, joule),
+            ("W", 1.0, watt),
+            ("kW", 1e3, watt),
+            ("MW", 1e6, watt),
+            ("hp", 745.699_871_582_270_2, watt),
+            // electricity
+            ("C", 1.0, a * s),
+            ("V", 1.0, volt),
+            ("ohm", 1.0, volt / a),
+        ];
+        for (name, scale, dim) in units {
+            vars.insert(
+                String::from(name),
+                Variant::Num(Quantity::unit(name, scale, dim)),
+            );
+        }
+
+        vars.insert(
+            String::from("abs"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                Ok(args[0].abs())
+            }),
+        );
+        vars.insert(
+            String::from("sqrt"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                if args[0].value < 0.0 {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                args[0]
+                    .clone()
+                    .try_pow(Quantity::from(0.5))
+                    .map_err(|err| InterpretError::from_num_error(err, id))
+            }),
+        );
+        vars.insert(
+            String::from("si"),
+            Variant::Function(|id, args| {
+                // the quantity in SI base units, such as 1 km/h in m/s
+                ensure_arg_count(1, 1, args.len(), id)?;
+                Ok(args[0].to_si())
+            }),
+        );
+        dimensionless_fn!(vars, "exp", f64::exp);
+        dimensionless_fn!(vars, "ln", f64::ln);
+        dimensionless_fn!(vars, "log10", f64::log10);
+        dimensionless_trig_fn!(vars, "sin", f64::sin);
+        dimensionless_trig_fn!(vars, "cos", f64::cos);
+        dimensionless_trig_fn!(vars, "tan", f64::tan);
+        vars.insert(
+            String::from("deg"),
+            Variant::AngleFunction(|id, args, mode| {
+                // converts degrees to the angle mode, this is what 90° and 90deg mean
+                ensure_arg_count(1, 1, args.len(), id)?;
+                if !args[0].dim.is_none() {
+                    return Err(InterpretError::DimensionMismatch(id.into()));
+                }
+                Ok(Quantity::from(
+                    mode.from_radians(args[0].si_value().to_radians()),
+                ))
+            }),
+        );
+        register_lazy_builtins(vars);
+        interpreter
+    }
+}
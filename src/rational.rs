@@ -0,0 +1,404 @@
+use crate::{
+    ensure_arg_count, register_lazy_builtins, AngleMode, InterpretError, Interpreter, Num,
+    NumError, Variant,
+};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive};
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, Sub, SubAssign};
+use std::str::FromStr;
+
+// Larger results than this many bits are reported as overflow instead of exhausting memory
+const MAX_POW_BITS: u64 = 1 << 20;
+// Largest exponent accepted by FromStr, such as the 3 in 1.5e3
+const MAX_EXPONENT: u32 = 10_000;
+// Largest factorial that is computed exactly
+const MAX_FACTORIAL: u32 = 10_000;
+// The solver rounds its guesses to multiples of 2^-APPROXIMATE_BITS
+const APPROXIMATE_BITS: u32 = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rational(pub BigRational);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParseRationalError;
+
+impl Rational {
+    pub fn new(numer: BigInt, denom: BigInt) -> Rational {
+        Rational(BigRational::new(numer, denom))
+    }
+
+    pub fn from_integer(n: BigInt) -> Rational {
+        Rational(BigRational::from_integer(n))
+    }
+
+    // The exact value of a float, or None if it is NaN or infinite
+    pub fn from_f64(x: f64) -> Option<Rational> {
+        BigRational::from_float(x).map(Rational)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.0.to_f64().unwrap_or(f64::NAN)
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        self.0.numer()
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        self.0.denom()
+    }
+
+    // Writes up to `max_digits` decimals, and whether that is the exact value
+    pub fn to_decimal(&self, max_digits: usize) -> (String, bool) {
+        let sign = if self.0.is_negative() { "-" } else { "" };
+        let (int, mut rem) = (self.0.abs().trunc().to_integer(), self.0.abs().fract());
+        let mut digits = String::new();
+        while !rem.is_integer() && digits.len() < max_digits {
+            rem *= BigRational::from_integer(BigInt::from(10));
+            digits.push_str(&rem.trunc().to_integer().to_string());
+            rem = rem.fract();
+        }
+        let exact = rem.is_integer();
+        if digits.is_empty() {
+            (format!("{}{}", sign, int), exact)
+        } else {
+            (format!("{}{}.{}", sign, int, digits), exact)
+        }
+    }
+
+    // The root-th root, if it is rational
+    fn exact_root(&self, root: u32) -> Option<Rational> {
+        if self.0.is_negative() && root.is_multiple_of(2) {
+            return None;
+        }
+        let numer = self.numer().nth_root(root);
+        let denom = self.denom().nth_root(root);
+        if BigInt::pow(&numer, root) == *self.numer() && BigInt::pow(&denom, root) == *self.denom()
+        {
+            Some(Rational::new(numer, denom))
+        } else {
+            None
+        }
+    }
+
+    fn checked_powi(&self, exponent: &BigInt) -> Result<Rational, NumError> {
+        let exponent = i32::try_from(exponent).map_err(|_| NumError::Overflow)?;
+        if *self.numer() == BigInt::ZERO && exponent < 0 {
+            return Err(NumError::Domain);
+        }
+        let bits = self.numer().bits().max(self.denom().bits());
+        if bits > 1 && bits * u64::from(exponent.unsigned_abs()) > MAX_POW_BITS {
+            return Err(NumError::Overflow);
+        }
+        Ok(Rational(self.0.pow(exponent)))
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(n: BigInt) -> Rational {
+        Rational::from_integer(n)
+    }
+}
+
+// Parses fractions like -1/3 and decimals like 0.1 or 1.5e-3
+impl FromStr for Rational {
+    type Err = ParseRationalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((numer, denom)) = s.split_once('/') {
+            let numer: BigInt = numer.trim().parse().map_err(|_| ParseRationalError)?;
+            let denom: BigInt = denom.trim().parse().map_err(|_| ParseRationalError)?;
+            if denom == BigInt::ZERO {
+                return Err(ParseRationalError);
+            }
+            return Ok(Rational::new(numer, denom));
+        }
+
+        let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (
+                mantissa,
+                exponent.parse::<i32>().map_err(|_| ParseRationalError)?,
+            ),
+            None => (s, 0),
+        };
+        if exponent.unsigned_abs() > MAX_EXPONENT {
+            return Err(ParseRationalError);
+        }
+        let (int, fract) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits: BigInt = format!("{}{}", int, fract)
+            .parse()
+            .map_err(|_| ParseRationalError)?;
+        let scale = exponent - fract.len() as i32;
+        let ten = BigRational::from_integer(BigInt::from(10));
+        Ok(Rational(BigRational::from_integer(digits) * ten.pow(scale)))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_integer() {
+            write!(f, "{}", self.numer())
+        } else {
+            write!(f, "{}/{}", self.numer(), self.denom())
+        }
+    }
+}
+
+macro_rules! impl_op {
+    ($($trait:ident $method:ident $assign_trait:ident $assign_method:ident),*) => {$(
+        impl $trait for Rational {
+            type Output = Rational;
+
+            fn $method(self, other: Rational) -> Rational {
+                Rational(self.0.$method(other.0))
+            }
+        }
+
+        impl $assign_trait for Rational {
+            fn $assign_method(&mut self, other: Rational) {
+                self.0.$assign_method(other.0)
+            }
+        }
+    )*};
+}
+
+impl_op!(
+    Add add AddAssign add_assign,
+    Sub sub SubAssign sub_assign,
+    Mul mul MulAssign mul_assign,
+    Div div DivAssign div_assign
+);
+
+impl Rem for Rational {
+    type Output = Rational;
+
+    fn rem(self, other: Rational) -> Rational {
+        Rational(self.0 % other.0)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational(-self.0)
+    }
+}
+
+impl Num for Rational {
+    #[inline(always)]
+    fn zero() -> Self {
+        Rational::from(BigInt::ZERO)
+    }
+    #[inline(always)]
+    fn one() -> Self {
+        Rational::from(BigInt::from(1))
+    }
+    #[inline(always)]
+    fn is_whole(&self) -> bool {
+        self.0.is_integer()
+    }
+    // This panics on 0 to a negative power and huge exponents, the interpreter uses try_pow
+    fn pow(self, other: Self) -> Self {
+        self.try_pow(other).expect("exponent out of range")
+    }
+
+    fn try_neg(self) -> Result<Self, NumError> {
+        Ok(-self)
+    }
+    fn try_div(self, other: Self) -> Result<Self, NumError> {
+        if other == Rational::zero() {
+            return Err(NumError::Domain);
+        }
+        Ok(self / other)
+    }
+    fn try_rem(self, other: Self) -> Result<Self, NumError> {
+        if other == Rational::zero() {
+            return Err(NumError::Domain);
+        }
+        Ok(self % other)
+    }
+    // Every Newton step would otherwise multiply the size of the denominator
+    fn approximate(self) -> Self {
+        let scale = BigRational::from_integer(BigInt::from(1) << APPROXIMATE_BITS);
+        Rational((self.0 * &scale).round() / scale)
+    }
+    fn try_pow(self, other: Self) -> Result<Self, NumError> {
+        if other.is_whole() {
+            return self.checked_powi(other.numer());
+        }
+        // 8^(2/3) is exact, 2^(1/2) and roots too deep to take exactly fall back to floats
+        if let Some(root) = u32::try_from(other.denom())
+            .ok()
+            .and_then(|root| self.exact_root(root))
+        {
+            return root.checked_powi(other.numer());
+        }
+        float_result(self.to_f64().powf(other.to_f64()))
+    }
+}
+
+// Functions without exact results are computed with floats
+fn float_result(x: f64) -> Result<Rational, NumError> {
+    if x.is_nan() {
+        Err(NumError::Domain)
+    } else {
+        Rational::from_f64(x).ok_or(NumError::Overflow)
+    }
+}
+
+// Registers a function that is computed with floats, checking that its argument is within the domain
+macro_rules! float_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        float_fn!($vars, $name, $func, |_| true)
+    };
+    ($vars:ident, $name:literal, $func:expr, $domain:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let x = args[0].to_f64();
+                if !$domain(x) {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                float_result($func(x)).map_err(|err| InterpretError::from_num_error(err, id))
+            }),
+        )
+    };
+}
+
+// Registers a float trigonometric function, converting its argument from the angle mode to radians
+macro_rules! float_trig_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::AngleFunction(|id, args, mode| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                float_result($func(mode.to_radians(args[0].to_f64())))
+                    .map_err(|err| InterpretError::from_num_error(err, id))
+            }),
+        )
+    };
+}
+
+// Registers an exact function of one argument
+macro_rules! exact_fn {
+    ($vars:ident, $name:literal, $func:expr) => {
+        $vars.insert(
+            String::from($name),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                Ok(Rational($func(&args[0].0)))
+            }),
+        )
+    };
+}
+
+impl Default for Interpreter<Rational> {
+    fn default() -> Self {
+        let mut interpreter = Interpreter::new();
+        let vars = &mut interpreter.vars;
+        // irrational constants are the exact value of their closest f64
+        for (name, value) in [
+            ("pi", std::f64::consts::PI),
+            ("e", std::f64::consts::E),
+            ("tau", std::f64::consts::TAU),
+            ("phi", 1.618_033_988_749_895_f64),
+        ] {
+            vars.insert(
+                String::from(name),
+                Variant::Num(Rational::from_f64(value).unwrap()),
+            );
+        }
+        let lightspeed = Rational::from(BigInt::from(299792458));
+        vars.insert(String::from("lightspeed"), Variant::Num(lightspeed)); // the speed of light in vacuum in m/s
+
+        // exact
+        exact_fn!(vars, "abs", BigRational::abs);
+        exact_fn!(vars, "floor", BigRational::floor);
+        exact_fn!(vars, "ceil", BigRational::ceil);
+        exact_fn!(vars, "round", BigRational::round);
+        exact_fn!(vars, "trunc", BigRational::trunc);
+        exact_fn!(vars, "sign", BigRational::signum);
+        exact_fn!(vars, "num", |x: &BigRational| BigRational::from_integer(
+            x.numer().clone()
+        ));
+        exact_fn!(vars, "den", |x: &BigRational| BigRational::from_integer(
+            x.denom().clone()
+        ));
+        vars.insert(
+            String::from("sqrt"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                if args[0] < Rational::zero() {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                match args[0].exact_root(2) {
+                    Some(root) => Ok(root),
+                    None => float_result(args[0].to_f64().sqrt())
+                        .map_err(|err| InterpretError::from_num_error(err, id)),
+                }
+            }),
+        );
+        vars.insert(
+            String::from("min"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, usize::MAX, args.len(), id)?;
+                Ok(args.iter().min().unwrap().clone())
+            }),
+        );
+        vars.insert(
+            String::from("max"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, usize::MAX, args.len(), id)?;
+                Ok(args.iter().max().unwrap().clone())
+            }),
+        );
+        register_lazy_builtins(vars);
+        vars.insert(
+            String::from("factorial"),
+            Variant::Function(|id, args| {
+                ensure_arg_count(1, 1, args.len(), id)?;
+                let n = &args[0];
+                if !n.is_whole() || *n < Rational::zero() {
+                    return Err(InterpretError::DomainError(id.into()));
+                }
+                let n = match n.numer().to_u32() {
+                    Some(n) if n <= MAX_FACTORIAL => n,
+                    _ => return Err(InterpretError::Overflow(id.into())),
+                };
+                Ok(Rational::from(
+                    (1..=n).map(BigInt::from).product::<BigInt>(),
+                ))
+            }),
+        );
+
+        // computed with floats
+        float_fn!(vars, "cbrt", f64::cbrt);
+        float_fn!(vars, "exp", f64::exp);
+        float_fn!(vars, "ln", f64::ln, |x| x > 0.0);
+        float_fn!(vars, "log10", f64::log10, |x| x > 0.0);
+        float_fn!(vars, "log2", f64::log2, |x| x > 0.0);
+        float_trig_fn!(vars, "sin", f64::sin);
+        float_trig_fn!(vars, "cos", f64::cos);
+        float_trig_fn!(vars, "tan", f64::tan);
+        vars.insert(
+            String::from("deg"),
+            Variant::AngleFunction(|id, args, mode| {
+                // converts degrees to the angle mode, this is what 90° and 90deg mean
+                ensure_arg_count(1, 1, args.len(), id)?;
+                Ok(match mode {
+                    AngleMode::Degrees => args[0].clone(),
+                    AngleMode::Gradians => {
+                        args[0].clone() * Rational::new(BigInt::from(10), BigInt::from(9))
+                    }
+                    AngleMode::Radians => float_result(args[0].to_f64().to_radians())
+                        .map_err(|err| InterpretError::from_num_error(err, id))?,
+                })
+            }),
+        );
+        interpreter
+    }
+}
@@ -0,0 +1,315 @@
+use crate::expr::{COND, EQ, FACTOR, POW, UNARY};
+use crate::{Expr, Num, OpVal};
+use std::fmt::Display;
+
+// Identifiers that are written as Greek letters
+const GREEK: [(&str, char); 24] = [
+    ("alpha", 'α'),
+    ("beta", 'β'),
+    ("gamma", 'γ'),
+    ("delta", 'δ'),
+    ("epsilon", 'ε'),
+    ("zeta", 'ζ'),
+    ("eta", 'η'),
+    ("theta", 'θ'),
+    ("iota", 'ι'),
+    ("kappa", 'κ'),
+    ("lambda", 'λ'),
+    ("mu", 'μ'),
+    ("nu", 'ν'),
+    ("xi", 'ξ'),
+    ("omicron", 'ο'),
+    ("pi", 'π'),
+    ("rho", 'ρ'),
+    ("sigma", 'σ'),
+    ("tau", 'τ'),
+    ("upsilon", 'υ'),
+    ("phi", 'φ'),
+    ("chi", 'χ'),
+    ("psi", 'ψ'),
+    ("omega", 'ω'),
+];
+
+// Builtins that LaTeX has an operator name for
+const LATEX_FUNCTIONS: [(&str, &str); 18] = [
+    ("sin", r"\sin"),
+    ("cos", r"\cos"),
+    ("tan", r"\tan"),
+    ("sec", r"\sec"),
+    ("csc", r"\csc"),
+    ("cot", r"\cot"),
+    ("asin", r"\arcsin"),
+    ("acos", r"\arccos"),
+    ("atan", r"\arctan"),
+    ("sinh", r"\sinh"),
+    ("cosh", r"\cosh"),
+    ("tanh", r"\tanh"),
+    ("exp", r"\exp"),
+    ("ln", r"\ln"),
+    ("log", r"\log"),
+    ("log10", r"\log_{10}"),
+    ("log2", r"\log_{2}"),
+    ("sign", r"\operatorname{sgn}"),
+];
+
+// Fractions and cases are laid out in two dimensions, so they only need parentheses as a base
+fn precedence<N: Num + Display>(expr: &Expr<N>) -> u8 {
+    match expr {
+        Expr::Op(OpVal::Div, ..) | Expr::If(..) => POW,
+        _ => expr.precedence(),
+    }
+}
+
+// A negation of something that starts with a minus is parenthesized, so -(-3) is not written --3
+fn negated<N: Num + Display>(expr: &Expr<N>) -> u8 {
+    if expr.precedence() == UNARY {
+        FACTOR
+    } else {
+        UNARY
+    }
+}
+
+// The branches of nested conditionals, as one list of (value, condition) with a final otherwise
+fn cases<N: Num>(expr: &Expr<N>) -> Vec<(&Expr<N>, Option<&Expr<N>>)> {
+    let mut cases = Vec::new();
+    let mut expr = expr;
+    while let Expr::If(cond, then, otherwise) = expr {
+        cases.push((then.as_ref(), Some(cond.as_ref())));
+        expr = otherwise;
+    }
+    cases.push((expr, None));
+    cases
+}
+
+// Returns expr as LaTeX math, without the surrounding $ signs.
+// Builtins are written in their usual notation, like \sqrt{x}, \left|x\right| and x!.
+pub fn to_latex<N: Num + Display>(expr: &Expr<N>) -> String {
+    latex(expr, EQ)
+}
+
+fn latex<N: Num + Display>(expr: &Expr<N>, min: u8) -> String {
+    if precedence(expr) < min {
+        return format!(r"\left({}\right)", latex(expr, EQ));
+    }
+    match expr {
+        Expr::Eq(lhs, rhs, _) => format!("{} = {}", latex(lhs, EQ), latex(rhs, COND)),
+        Expr::FuncDef(id, params, body, _) => {
+            let params: Vec<String> = params.iter().map(|param| latex_id(param)).collect();
+            format!(
+                r"{}\left({}\right) = {}",
+                latex_function(id),
+                params.join(", "),
+                latex(body, COND)
+            )
+        }
+        Expr::FuncOrVarMul(id, args, _) => match (id.as_str(), args.as_slice()) {
+            ("sqrt", [u]) => format!(r"\sqrt{{{}}}", latex(u, EQ)),
+            ("cbrt", [u]) => format!(r"\sqrt[3]{{{}}}", latex(u, EQ)),
+            ("abs", [u]) => format!(r"\left|{}\right|", latex(u, EQ)),
+            ("factorial", [u]) => format!("{}!", latex(u, FACTOR)),
+            ("deg", [u]) => format!(r"{}^\circ", latex(u, FACTOR)),
+            ("log", [u, base]) => {
+                format!(r"\log_{{{}}}\left({}\right)", latex(base, EQ), latex(u, EQ))
+            }
+            _ => {
+                let args: Vec<String> = args.iter().map(|arg| latex(arg, EQ)).collect();
+                format!(r"{}\left({}\right)", latex_function(id), args.join(", "))
+            }
+        },
+        Expr::If(..) => {
+            let rows: Vec<String> = cases(expr)
+                .into_iter()
+                .map(|(value, cond)| match cond {
+                    Some(cond) => {
+                        format!(r"{} & \text{{if }} {}", latex(value, EQ), latex(cond, EQ))
+                    }
+                    None => format!(r"{} & \text{{otherwise}}", latex(value, EQ)),
+                })
+                .collect();
+            format!(r"\begin{{cases}} {} \end{{cases}}", rows.join(r" \\ "))
+        }
+        Expr::Neg(u, _) => format!("-{}", latex(u, negated(u))),
+        Expr::Num(n) => n.to_string(),
+        Expr::Op(OpVal::Div, lhs, rhs, _) => {
+            format!(r"\frac{{{}}}{{{}}}", latex(lhs, EQ), latex(rhs, EQ))
+        }
+        Expr::Op(OpVal::Pow, lhs, rhs, _) => {
+            format!("{}^{{{}}}", latex(lhs, FACTOR), latex(rhs, EQ))
+        }
+        Expr::Op(op, lhs, rhs, _) => {
+            let precedence = expr.precedence();
+            let symbol = match op {
+                OpVal::Mul => r"\cdot",
+                OpVal::Mod => r"\bmod",
+                OpVal::In => r"\to",
+                OpVal::LessEq => r"\leq",
+                OpVal::GreaterEq => r"\geq",
+                OpVal::Equal => "=",
+                OpVal::NotEqual => r"\neq",
+                OpVal::And => r"\land",
+                OpVal::Or => r"\lor",
+                op => op.symbol(),
+            };
+            format!(
+                "{} {} {}",
+                latex(lhs, precedence),
+                symbol,
+                latex(rhs, precedence + 1)
+            )
+        }
+        Expr::Var(id, _) => latex_id(id),
+    }
+}
+
+fn latex_id(id: &str) -> String {
+    // LaTeX has no \omicron, because it looks like a Latin o
+    if id == "omicron" {
+        String::from("o")
+    } else if let Some((name, _)) = GREEK.iter().find(|(name, _)| *name == id) {
+        format!(r"\{}", name)
+    } else if id.chars().count() == 1 {
+        id.to_string()
+    } else {
+        format!(r"\mathrm{{{}}}", id.replace('_', r"\_"))
+    }
+}
+
+fn latex_function(id: &str) -> String {
+    match LATEX_FUNCTIONS.iter().find(|(name, _)| *name == id) {
+        Some((_, command)) => command.to_string(),
+        None if id.chars().count() == 1 => id.to_string(),
+        None => format!(r"\operatorname{{{}}}", id.replace('_', r"\_")),
+    }
+}
+
+// Returns expr as a presentation MathML <math> element
+pub fn to_mathml<N: Num + Display>(expr: &Expr<N>) -> String {
+    format!(
+        r#"<math xmlns="http://www.w3.org/1998/Math/MathML">{}</math>"#,
+        mathml(expr, EQ)
+    )
+}
+
+// Every element is a single child, so it can be the numerator of <mfrac> or the base of <msup>
+fn mathml<N: Num + Display>(expr: &Expr<N>, min: u8) -> String {
+    if precedence(expr) < min {
+        return fenced(&mathml(expr, EQ));
+    }
+    match expr {
+        Expr::Eq(lhs, rhs, _) => format!(
+            "<mrow>{}<mo>=</mo>{}</mrow>",
+            mathml(lhs, EQ),
+            mathml(rhs, COND)
+        ),
+        Expr::FuncDef(id, params, body, _) => {
+            let params: Vec<String> = params.iter().map(|param| mi(param)).collect();
+            format!(
+                "<mrow>{}<mo>&#x2061;</mo>{}<mo>=</mo>{}</mrow>",
+                mi(id),
+                fenced(&params.join("<mo>,</mo>")),
+                mathml(body, COND)
+            )
+        }
+        Expr::FuncOrVarMul(id, args, _) => match (id.as_str(), args.as_slice()) {
+            ("sqrt", [u]) => format!("<msqrt>{}</msqrt>", mathml(u, EQ)),
+            ("cbrt", [u]) => format!("<mroot>{}<mn>3</mn></mroot>", mathml(u, EQ)),
+            ("abs", [u]) => format!("<mrow><mo>|</mo>{}<mo>|</mo></mrow>", mathml(u, EQ)),
+            ("factorial", [u]) => format!("<mrow>{}<mo>!</mo></mrow>", mathml(u, FACTOR)),
+            ("deg", [u]) => format!("<msup>{}<mo>°</mo></msup>", mathml(u, FACTOR)),
+            ("log", [u, base]) => format!(
+                "<mrow><msub><mi>log</mi>{}</msub><mo>&#x2061;</mo>{}</mrow>",
+                mathml(base, EQ),
+                fenced(&mathml(u, EQ))
+            ),
+            _ => {
+                let function = match id.as_str() {
+                    "log10" => String::from("<msub><mi>log</mi><mn>10</mn></msub>"),
+                    "log2" => String::from("<msub><mi>log</mi><mn>2</mn></msub>"),
+                    id => mi(id),
+                };
+                let args: Vec<String> = args.iter().map(|arg| mathml(arg, EQ)).collect();
+                format!(
+                    "<mrow>{}<mo>&#x2061;</mo>{}</mrow>",
+                    function,
+                    fenced(&args.join("<mo>,</mo>"))
+                )
+            }
+        },
+        Expr::If(..) => {
+            let rows: String = cases(expr)
+                .into_iter()
+                .map(|(value, cond)| match cond {
+                    Some(cond) => format!(
+                        "<mtr><mtd>{}</mtd><mtd><mrow><mtext>if&#xA0;</mtext>{}</mrow></mtd></mtr>",
+                        mathml(value, EQ),
+                        mathml(cond, EQ)
+                    ),
+                    None => format!(
+                        "<mtr><mtd>{}</mtd><mtd><mtext>otherwise</mtext></mtd></mtr>",
+                        mathml(value, EQ)
+                    ),
+                })
+                .collect();
+            format!(
+                r#"<mrow><mo>{{</mo><mtable columnalign="left">{}</mtable></mrow>"#,
+                rows
+            )
+        }
+        Expr::Neg(u, _) => format!("<mrow><mo>−</mo>{}</mrow>", mathml(u, negated(u))),
+        Expr::Num(n) => {
+            let text = escape(&n.to_string());
+            match text.strip_prefix('-') {
+                Some(magnitude) => format!("<mrow><mo>−</mo><mn>{}</mn></mrow>", magnitude),
+                None => format!("<mn>{}</mn>", text),
+            }
+        }
+        Expr::Op(OpVal::Div, lhs, rhs, _) => {
+            format!("<mfrac>{}{}</mfrac>", mathml(lhs, EQ), mathml(rhs, EQ))
+        }
+        Expr::Op(OpVal::Pow, lhs, rhs, _) => {
+            format!("<msup>{}{}</msup>", mathml(lhs, FACTOR), mathml(rhs, EQ))
+        }
+        Expr::Op(op, lhs, rhs, _) => {
+            let precedence = expr.precedence();
+            let symbol = match op {
+                OpVal::Sub => "−",
+                OpVal::Mul => "⋅",
+                OpVal::Mod => "mod",
+                OpVal::In => "→",
+                OpVal::Less => "&lt;",
+                OpVal::LessEq => "≤",
+                OpVal::Greater => "&gt;",
+                OpVal::GreaterEq => "≥",
+                OpVal::Equal => "=",
+                OpVal::NotEqual => "≠",
+                OpVal::And => "∧",
+                OpVal::Or => "∨",
+                op => op.symbol(),
+            };
+            format!(
+                "<mrow>{}<mo>{}</mo>{}</mrow>",
+                mathml(lhs, precedence),
+                symbol,
+                mathml(rhs, precedence + 1)
+            )
+        }
+        Expr::Var(id, _) => mi(id),
+    }
+}
+
+fn mi(id: &str) -> String {
+    match GREEK.iter().find(|(name, _)| *name == id) {
+        Some((_, letter)) => format!("<mi>{}</mi>", letter),
+        None => format!("<mi>{}</mi>", escape(id)),
+    }
+}
+
+fn fenced(content: &str) -> String {
+    format!("<mrow><mo>(</mo>{}<mo>)</mo></mrow>", content)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,280 @@
+use crate::interpreter::truth;
+use crate::{Expr, Num, NumError, OpVal};
+use std::ops::Range;
+
+// Returns an equivalent expression that is cheaper to evaluate: constant operations are folded,
+// identities like x * 1 are removed and like terms such as 2x + 3x are combined.
+// Operands that cancel out or are multiplied by 0 are dropped, even if evaluating them would fail.
+pub fn simplify<N: Num>(expr: &Expr<N>) -> Expr<N> {
+    match expr {
+        Expr::Eq(lhs, rhs, span) => Expr::Eq(
+            Box::new(simplify(lhs)),
+            Box::new(simplify(rhs)),
+            span.clone(),
+        ),
+        Expr::FuncDef(id, params, body, span) => Expr::FuncDef(
+            id.clone(),
+            params.clone(),
+            Box::new(simplify(body)),
+            span.clone(),
+        ),
+        Expr::FuncOrVarMul(id, args, span) => Expr::FuncOrVarMul(
+            id.clone(),
+            args.iter().map(simplify).collect(),
+            span.clone(),
+        ),
+        Expr::If(cond, then, otherwise) => match simplify(cond) {
+            Expr::Num(n) if n != N::zero() => simplify(then),
+            Expr::Num(_) => simplify(otherwise),
+            cond => Expr::If(
+                Box::new(cond),
+                Box::new(simplify(then)),
+                Box::new(simplify(otherwise)),
+            ),
+        },
+        Expr::Neg(expr, span) => combine_sum(Expr::Neg(Box::new(simplify(expr)), span.clone())),
+        Expr::Num(n) => Expr::Num(n.clone()),
+        Expr::Op(op, lhs, rhs, span) => {
+            let (lhs, rhs) = (simplify(lhs), simplify(rhs));
+            if let (Expr::Num(a), Expr::Num(b)) = (&lhs, &rhs) {
+                if let Some(n) = fold(*op, a.clone(), b.clone()) {
+                    return Expr::Num(n);
+                }
+            }
+            let expr = Expr::Op(*op, Box::new(lhs), Box::new(rhs), span.clone());
+            match op {
+                OpVal::Add | OpVal::Sub => combine_sum(expr),
+                OpVal::Mul => combine_product(expr),
+                _ => remove_identity(expr),
+            }
+        }
+        Expr::Var(..) => expr.clone(),
+    }
+}
+
+// Folding is skipped when the interpreter would fail, or fail in strict mode
+fn finite<N: Num>(result: Result<N, NumError>) -> Option<N> {
+    result.ok().filter(|n| !n.is_nan() && !n.is_infinite())
+}
+
+fn fold<N: Num>(op: OpVal, a: N, b: N) -> Option<N> {
+    let zero = N::zero();
+    finite(match op {
+        OpVal::Add => a.try_add(b),
+        OpVal::Sub => a.try_sub(b),
+        OpVal::Mul => a.try_mul(b),
+        OpVal::Div | OpVal::Mod | OpVal::In if b == zero => return None,
+        OpVal::Div => a.try_div(b),
+        OpVal::Mod => a.try_rem(b),
+        OpVal::In => a.try_convert(b),
+        OpVal::Pow => a.try_pow(b),
+        OpVal::Less => Ok(truth(a < b)),
+        OpVal::LessEq => Ok(truth(a <= b)),
+        OpVal::Greater => Ok(truth(a > b)),
+        OpVal::GreaterEq => Ok(truth(a >= b)),
+        OpVal::Equal => Ok(truth(a == b)),
+        OpVal::NotEqual => Ok(truth(a != b)),
+        OpVal::And => Ok(truth(a != zero && b != zero)),
+        OpVal::Or => Ok(truth(a != zero || b != zero)),
+        OpVal::Eq | OpVal::Exclaim => return None,
+    })
+}
+
+fn is<N: Num>(expr: &Expr<N>, n: N) -> bool {
+    matches!(expr, Expr::Num(m) if *m == n)
+}
+
+fn remove_identity<N: Num>(expr: Expr<N>) -> Expr<N> {
+    let Expr::Op(op, lhs, rhs, span) = expr else {
+        return expr;
+    };
+    match op {
+        OpVal::Div | OpVal::Pow if is(&rhs, N::one()) => *lhs,
+        OpVal::Pow if is(&rhs, N::zero()) || is(&lhs, N::one()) => Expr::Num(N::one()),
+        // the rhs is never evaluated, like the interpreter short-circuits
+        OpVal::And if is(&lhs, N::zero()) => Expr::Num(N::zero()),
+        OpVal::Or if matches!(*lhs, Expr::Num(ref n) if *n != N::zero()) => Expr::Num(N::one()),
+        _ => Expr::Op(op, lhs, rhs, span),
+    }
+}
+
+// The terms of a sum, as coefficients of equal expressions and a constant
+struct Terms<N: Num> {
+    constant: N,
+    terms: Vec<(N, Expr<N>)>,
+}
+
+impl<N: Num> Terms<N> {
+    fn collect(&mut self, expr: &Expr<N>, positive: bool) -> Option<()> {
+        match expr {
+            Expr::Op(OpVal::Add, lhs, rhs, _) => {
+                self.collect(lhs, positive)?;
+                self.collect(rhs, positive)
+            }
+            Expr::Op(OpVal::Sub, lhs, rhs, _) => {
+                self.collect(lhs, positive)?;
+                self.collect(rhs, !positive)
+            }
+            Expr::Neg(expr, _) => self.collect(expr, !positive),
+            Expr::Num(n) => {
+                self.constant = add_signed(self.constant.clone(), n.clone(), positive)?;
+                Some(())
+            }
+            _ => {
+                // products keep their coefficient in front, so 2x and 3x are both terms of x
+                let (coefficient, term) = match expr {
+                    Expr::Op(OpVal::Mul, lhs, rhs, _) => match lhs.as_ref() {
+                        Expr::Num(n) => (n.clone(), rhs.as_ref()),
+                        _ => (N::one(), expr),
+                    },
+                    _ => (N::one(), expr),
+                };
+                match self.terms.iter_mut().find(|(_, other)| other == term) {
+                    Some((sum, _)) => *sum = add_signed(sum.clone(), coefficient, positive)?,
+                    None => {
+                        let coefficient = add_signed(N::zero(), coefficient, positive)?;
+                        self.terms.push((coefficient, term.clone()));
+                    }
+                }
+                Some(())
+            }
+        }
+    }
+}
+
+fn add_signed<N: Num>(acc: N, n: N, positive: bool) -> Option<N> {
+    finite(if positive {
+        acc.try_add(n)
+    } else {
+        acc.try_sub(n)
+    })
+}
+
+// Rebuilds a sum from its terms, subtracting the ones with negative coefficients
+fn combine_sum<N: Num>(expr: Expr<N>) -> Expr<N> {
+    let mut terms = Terms {
+        constant: N::zero(),
+        terms: Vec::new(),
+    };
+    // types without negative numbers cannot collect differences, so they are only cleaned up
+    if terms.collect(&expr, true).is_none() {
+        return match expr {
+            Expr::Op(OpVal::Add, lhs, rhs, _) if is(&lhs, N::zero()) => *rhs,
+            Expr::Op(OpVal::Add | OpVal::Sub, lhs, rhs, _) if is(&rhs, N::zero()) => *lhs,
+            Expr::Neg(expr, span) => match *expr {
+                Expr::Neg(expr, _) => *expr,
+                expr => Expr::Neg(Box::new(expr), span),
+            },
+            expr => expr,
+        };
+    }
+    let span = span_of(&expr);
+    let mut result = None;
+    let constant = (terms.constant != N::zero()).then_some((terms.constant, None));
+    for (coefficient, term) in terms
+        .terms
+        .into_iter()
+        .map(|(coefficient, term)| (coefficient, Some(term)))
+        .chain(constant)
+    {
+        if coefficient == N::zero() {
+            continue;
+        }
+        let negative = coefficient < N::zero();
+        let magnitude = if negative {
+            match coefficient.clone().try_neg() {
+                Ok(magnitude) => magnitude,
+                Err(_) => return expr,
+            }
+        } else {
+            coefficient.clone()
+        };
+        let value = match term {
+            None => Expr::Num(magnitude),
+            Some(term) if magnitude == N::one() => term,
+            Some(term) => mul(Expr::Num(magnitude), term, &span),
+        };
+        result = Some(match result {
+            None if negative => match value {
+                Expr::Num(_) => Expr::Num(coefficient),
+                value => Expr::Neg(Box::new(value), span.clone()),
+            },
+            None => value,
+            Some(sum) => {
+                let op = if negative { OpVal::Sub } else { OpVal::Add };
+                Expr::Op(op, Box::new(sum), Box::new(value), span.clone())
+            }
+        });
+    }
+    result.unwrap_or(Expr::Num(N::zero()))
+}
+
+// Multiplies the numbers of a product into one coefficient in front of the other factors
+fn combine_product<N: Num>(expr: Expr<N>) -> Expr<N> {
+    fn collect<N: Num>(
+        expr: &Expr<N>,
+        coefficient: &mut N,
+        factors: &mut Vec<Expr<N>>,
+    ) -> Option<()> {
+        match expr {
+            Expr::Op(OpVal::Mul, lhs, rhs, _) => {
+                collect(lhs, coefficient, factors)?;
+                collect(rhs, coefficient, factors)
+            }
+            Expr::Neg(expr, _) => {
+                *coefficient = finite(coefficient.clone().try_neg())?;
+                collect(expr, coefficient, factors)
+            }
+            Expr::Num(n) => {
+                *coefficient = finite(coefficient.clone().try_mul(n.clone()))?;
+                Some(())
+            }
+            _ => {
+                factors.push(expr.clone());
+                Some(())
+            }
+        }
+    }
+
+    let (mut coefficient, mut factors) = (N::one(), Vec::new());
+    if collect(&expr, &mut coefficient, &mut factors).is_none() {
+        return expr;
+    }
+    if coefficient == N::zero() {
+        return Expr::Num(coefficient);
+    }
+    let span = span_of(&expr);
+    let Some(product) = factors
+        .into_iter()
+        .reduce(|product, factor| mul(product, factor, &span))
+    else {
+        return Expr::Num(coefficient);
+    };
+    if coefficient == N::one() {
+        product
+    } else if N::one()
+        .try_neg()
+        .is_ok_and(|minus_one| coefficient == minus_one)
+    {
+        Expr::Neg(Box::new(product), span)
+    } else {
+        mul(Expr::Num(coefficient), product, &span)
+    }
+}
+
+fn mul<N: Num>(lhs: Expr<N>, rhs: Expr<N>, span: &Range<usize>) -> Expr<N> {
+    Expr::Op(OpVal::Mul, Box::new(lhs), Box::new(rhs), span.clone())
+}
+
+// New nodes point at the operator they replace
+fn span_of<N: Num>(expr: &Expr<N>) -> Range<usize> {
+    match expr {
+        Expr::Eq(.., span)
+        | Expr::FuncDef(.., span)
+        | Expr::FuncOrVarMul(.., span)
+        | Expr::Neg(.., span)
+        | Expr::Op(.., span)
+        | Expr::Var(.., span) => span.clone(),
+        Expr::If(..) | Expr::Num(_) => 0..0,
+    }
+}
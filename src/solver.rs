@@ -0,0 +1,524 @@
+use crate::{Expr, InterpretError, Interpreter, Num, NumError, OpVal, Variant};
+
+// Highest exponent that is expanded when building a polynomial
+const MAX_DEGREE: usize = 64;
+const MAX_ITERATIONS: usize = 100;
+// Where the numeric methods start, in order, until one of them converges to a root
+const STARTS: [i8; 8] = [1, -1, 2, -2, 10, -10, 100, -100];
+
+impl<N: Num> Interpreter<N> {
+    // Solves `lhs = rhs` for the only identifier that has no value yet, and binds it.
+    pub(crate) fn solve<'expr>(
+        &mut self,
+        lhs: &'expr Expr<N>,
+        rhs: &'expr Expr<N>,
+    ) -> Result<N, InterpretError<'expr>> {
+        let mut unknowns = Vec::with_capacity(1);
+        self.collect_unknowns(lhs, &mut unknowns);
+        self.collect_unknowns(rhs, &mut unknowns);
+        let id = match unknowns[..] {
+            [] => return Err(InterpretError::NoUnknown),
+            [id] => id,
+            [first, second, ..] => {
+                return Err(InterpretError::MultipleUnknowns(
+                    first.into(),
+                    second.into(),
+                ))
+            }
+        };
+
+        let result = match (self.polynomial(lhs, id)?, self.polynomial(rhs, id)?) {
+            (Some(lhs), Some(rhs)) => match poly_sub(lhs.clone(), rhs.clone()) {
+                Ok(poly) => solve_polynomial(id, poly),
+                // types without negative numbers, like Unsigned, cannot move rhs to the left
+                Err(_) if N::one().try_neg().is_err() => solve_unsigned(id, &lhs, &rhs),
+                Err(err) => Err(InterpretError::from_num_error(err, id)),
+            },
+            _ => self.solve_numeric(id, lhs, rhs),
+        };
+        match result {
+            Ok(n) => {
+                self.vars.insert(id.to_string(), Variant::Num(n.clone()));
+                Ok(n)
+            }
+            Err(err) => {
+                self.vars.remove(id);
+                Err(err)
+            }
+        }
+    }
+
+    fn collect_unknowns<'expr>(&self, expr: &'expr Expr<N>, unknowns: &mut Vec<&'expr str>) {
+        match expr {
+            Expr::Eq(lhs, rhs, _) | Expr::Op(_, lhs, rhs, _) => {
+                self.collect_unknowns(lhs, unknowns);
+                self.collect_unknowns(rhs, unknowns);
+            }
+            Expr::FuncDef(..) => {}
+            Expr::FuncOrVarMul(id, args, _) => {
+                if !self.vars.contains_key(id) && !unknowns.contains(&id.as_str()) {
+                    unknowns.push(id);
+                }
+                for arg in args {
+                    self.collect_unknowns(arg, unknowns);
+                }
+            }
+            Expr::If(cond, then, otherwise) => {
+                self.collect_unknowns(cond, unknowns);
+                self.collect_unknowns(then, unknowns);
+                self.collect_unknowns(otherwise, unknowns);
+            }
+            Expr::Neg(expr, _) => self.collect_unknowns(expr, unknowns),
+            Expr::Num(_) => {}
+            Expr::Var(id, _) => {
+                if !self.vars.contains_key(id) && !unknowns.contains(&id.as_str()) {
+                    unknowns.push(id);
+                }
+            }
+        }
+    }
+
+    // Returns the coefficients (lowest degree first) of `expr` as a polynomial in `x`,
+    // or None if `expr` is not a polynomial in `x`.
+    fn polynomial<'expr>(
+        &mut self,
+        expr: &'expr Expr<N>,
+        x: &'expr str,
+    ) -> Result<Option<Vec<N>>, InterpretError<'expr>> {
+        if !contains(expr, x) {
+            return Ok(Some(poly_trim(vec![self.eval(expr)?])));
+        }
+        let poly = match expr {
+            Expr::Var(..) => Ok(vec![N::zero(), N::one()]),
+            // x(arg) is x times arg
+            Expr::FuncOrVarMul(id, args, _) if id == x && args.len() == 1 => {
+                match self.polynomial(&args[0], x)? {
+                    Some(arg) => poly_mul(&[N::zero(), N::one()], &arg),
+                    None => return Ok(None),
+                }
+            }
+            Expr::Neg(expr, _) => match self.polynomial(expr, x)? {
+                Some(poly) => poly_neg(poly),
+                None => return Ok(None),
+            },
+            Expr::Op(op, lhs, rhs, _) => {
+                let lhs_poly = match self.polynomial(lhs, x)? {
+                    Some(p) => p,
+                    None => return Ok(None),
+                };
+                match op {
+                    OpVal::Add | OpVal::Sub | OpVal::Mul => {
+                        let rhs_poly = match self.polynomial(rhs, x)? {
+                            Some(p) => p,
+                            None => return Ok(None),
+                        };
+                        match op {
+                            OpVal::Add => poly_add(lhs_poly, rhs_poly),
+                            OpVal::Sub => poly_sub(lhs_poly, rhs_poly),
+                            _ => poly_mul(&lhs_poly, &rhs_poly),
+                        }
+                    }
+                    // dividing the coefficients would round them away if division truncates
+                    OpVal::Div if !contains(rhs, x) && !truncates::<N>() => {
+                        let divisor = self.eval(rhs)?;
+                        if divisor == N::zero() {
+                            return Ok(None);
+                        }
+                        lhs_poly
+                            .into_iter()
+                            .map(|c| c.try_div(divisor.clone()))
+                            .collect()
+                    }
+                    OpVal::Pow if !contains(rhs, x) => match as_exponent(self.eval(rhs)?) {
+                        Some(exponent) => poly_pow(&lhs_poly, exponent),
+                        None => return Ok(None),
+                    },
+                    _ => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+        poly.map(Some)
+            .map_err(|err| InterpretError::from_num_error(err, x))
+    }
+
+    fn solve_numeric<'expr>(
+        &mut self,
+        id: &'expr str,
+        lhs: &'expr Expr<N>,
+        rhs: &'expr Expr<N>,
+    ) -> Result<N, InterpretError<'expr>> {
+        let mut first_err = None;
+        for start in STARTS {
+            match self.secant(id, start, lhs, rhs) {
+                Ok(Some(x)) => return Ok(x),
+                Ok(None) => {}
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+        Err(first_err.unwrap_or(InterpretError::NoSolution(id.into())))
+    }
+
+    // Secant method, starting from x = start and a point close to it
+    fn secant<'expr>(
+        &mut self,
+        id: &'expr str,
+        start: i8,
+        lhs: &'expr Expr<N>,
+        rhs: &'expr Expr<N>,
+    ) -> Result<Option<N>, InterpretError<'expr>> {
+        let Ok(mut x0) = from_i8::<N>(start) else {
+            return Ok(None);
+        };
+        // close to start, so the first step follows the slope there, unless division truncates
+        let step = if truncates::<N>() {
+            N::one()
+        } else {
+            N::one() / from_usize(16)
+        };
+        let mut x1 = x0.clone() + step;
+        let mut f0 = self.eval_at(id, x0.clone(), lhs, rhs)?;
+        let mut f1 = self.eval_at(id, x1.clone(), lhs, rhs)?;
+        for _ in 0..MAX_ITERATIONS {
+            if f1 == N::zero() || f1 == f0 {
+                break;
+            }
+            let next = secant_step(x0, x1.clone(), f0, f1.clone())
+                .map_err(|err| InterpretError::from_num_error(err, id))?
+                .approximate();
+            if next == x1 {
+                break;
+            }
+            x0 = x1;
+            f0 = f1;
+            x1 = next;
+            f1 = self.eval_at(id, x1.clone(), lhs, rhs)?;
+        }
+        let (l, r) = self.eval_sides(id, x1.clone(), lhs, rhs)?;
+        let root = l
+            .clone()
+            .try_sub(r.clone())
+            .and_then(|fx| is_root(fx, abs(l)?.try_add(abs(r)?)?));
+        match root {
+            Ok(true) => Ok(Some(x1)),
+            Ok(false) => Ok(None),
+            Err(err) => Err(InterpretError::from_num_error(err, id)),
+        }
+    }
+
+    fn eval_at<'expr>(
+        &mut self,
+        id: &'expr str,
+        x: N,
+        lhs: &'expr Expr<N>,
+        rhs: &'expr Expr<N>,
+    ) -> Result<N, InterpretError<'expr>> {
+        let (l, r) = self.eval_sides(id, x, lhs, rhs)?;
+        l.try_sub(r)
+            .map_err(|err| InterpretError::from_num_error(err, id))
+    }
+
+    fn eval_sides<'expr>(
+        &mut self,
+        id: &'expr str,
+        x: N,
+        lhs: &'expr Expr<N>,
+        rhs: &'expr Expr<N>,
+    ) -> Result<(N, N), InterpretError<'expr>> {
+        self.vars.insert(id.to_string(), Variant::Num(x));
+        Ok((self.eval(lhs)?, self.eval(rhs)?))
+    }
+}
+
+fn solve_polynomial<N: Num>(id: &str, poly: Vec<N>) -> Result<N, InterpretError<'_>> {
+    let root = match poly.len() {
+        0 => return Err(InterpretError::InfiniteSolutions(id.into())),
+        1 => return Err(InterpretError::NoSolution(id.into())),
+        2 => poly[0]
+            .clone()
+            .try_neg()
+            .and_then(|c| c.try_div(poly[1].clone()))
+            .map(Some),
+        3 if !truncates::<N>() => quadratic(&poly),
+        _ => return solve_iteratively(id, &poly),
+    };
+    // the formulas are exact unless division truncates
+    let root = root.and_then(|root| match root {
+        Some(x) if truncates::<N>() => Ok(poly_is_root(&poly, &x)?.then_some(x)),
+        root => Ok(root),
+    });
+    match root {
+        Ok(Some(x)) => Ok(x),
+        Ok(None) => Err(InterpretError::NoSolution(id.into())),
+        Err(err) => Err(InterpretError::from_num_error(err, id)),
+    }
+}
+
+// Runs Newton's method from each start until one of them converges to a root
+fn solve_iteratively<'a, N: Num>(id: &'a str, poly: &[N]) -> Result<N, InterpretError<'a>> {
+    let mut first_err = None;
+    for start in STARTS {
+        let root = from_i8(start).and_then(|start| {
+            let x = newton(poly, start)?;
+            Ok(poly_is_root(poly, &x)?.then_some(x))
+        });
+        match root {
+            Ok(Some(x)) => return Ok(x),
+            Ok(None) => {}
+            // types without negative numbers cannot start below 0
+            Err(_) if start < 0 => {}
+            Err(err) => {
+                first_err.get_or_insert(err);
+            }
+        }
+    }
+    match first_err {
+        Some(err) => Err(InterpretError::from_num_error(err, id)),
+        None => Err(InterpretError::NoSolution(id.into())),
+    }
+}
+
+// Newton's method on the sign and magnitude of lhs - rhs, for types without negative numbers
+fn solve_unsigned<'a, N: Num>(id: &'a str, lhs: &[N], rhs: &[N]) -> Result<N, InterpretError<'a>> {
+    let root = poly_derive(lhs).and_then(|dlhs| {
+        let drhs = poly_derive(rhs)?;
+        let mut x = N::one();
+        for _ in 0..MAX_ITERATIONS {
+            let (fx, fx_negative) = difference(lhs, rhs, &x)?;
+            let (dfx, dfx_negative) = difference(&dlhs, &drhs, &x)?;
+            if fx == N::zero() || dfx == N::zero() {
+                break;
+            }
+            let step = fx.try_div(dfx)?;
+            let step = if step == N::zero() { N::one() } else { step };
+            // stepping below 0 means the root is negative
+            let next = if fx_negative == dfx_negative {
+                x.clone().try_sub(step)
+            } else {
+                x.clone().try_add(step)
+            };
+            match next {
+                Ok(next) => x = next,
+                Err(_) => break,
+            }
+        }
+        Ok((difference(lhs, rhs, &x)?.0 == N::zero()).then_some(x))
+    });
+    match root {
+        Ok(Some(x)) => Ok(x),
+        Ok(None) => Err(InterpretError::NoSolution(id.into())),
+        Err(err) => Err(InterpretError::from_num_error(err, id)),
+    }
+}
+
+// The magnitude of lhs(x) - rhs(x), and whether it is negative
+fn difference<N: Num>(lhs: &[N], rhs: &[N], x: &N) -> Result<(N, bool), NumError> {
+    let (l, r) = (poly_eval(lhs, x)?, poly_eval(rhs, x)?);
+    if l < r {
+        Ok((r.try_sub(l)?, true))
+    } else {
+        Ok((l.try_sub(r)?, false))
+    }
+}
+
+// Quadratic formula, picking the larger root if they are ordered
+fn quadratic<N: Num>(poly: &[N]) -> Result<Option<N>, NumError> {
+    let two = N::one() + N::one();
+    let (c, b, a) = (poly[0].clone(), poly[1].clone(), poly[2].clone());
+    let four_ac = from_usize::<N>(4).try_mul(a.clone())?.try_mul(c)?;
+    let disc = b.clone().try_mul(b.clone())?.try_sub(four_ac)?;
+    // a negative discriminant only has a root for types like Complex
+    let root = match disc.try_pow(N::one() / two.clone()) {
+        Ok(root) if !root.is_nan() => root,
+        _ => return Ok(None),
+    };
+    let denom = two.try_mul(a)?;
+    let minus_b = b.try_neg()?;
+    let x1 = minus_b
+        .clone()
+        .try_add(root.clone())?
+        .try_div(denom.clone())?;
+    let x2 = minus_b.try_sub(root)?.try_div(denom)?;
+    Ok(Some(if x2 > x1 { x2 } else { x1 }))
+}
+
+// Newton's method, which stops early where the derivative is 0
+fn newton<N: Num>(poly: &[N], start: N) -> Result<N, NumError> {
+    let derivative = poly_derive(poly)?;
+    let mut x = start;
+    for _ in 0..MAX_ITERATIONS {
+        let fx = poly_eval(poly, &x)?;
+        let dfx = poly_eval(&derivative, &x)?;
+        if fx == N::zero() || dfx == N::zero() {
+            break;
+        }
+        let mut step = fx.clone().try_div(dfx.clone())?;
+        // truncating division can round the step away before the root is reached
+        if step == N::zero() && truncates::<N>() {
+            step = if (fx > N::zero()) == (dfx > N::zero()) {
+                N::one()
+            } else {
+                N::one().try_neg()?
+            };
+        }
+        let next = x.clone().try_sub(step)?.approximate();
+        if next == x {
+            break;
+        }
+        x = next;
+    }
+    Ok(x)
+}
+
+// x1 - f1 * (x1 - x0) / (f1 - f0)
+fn secant_step<N: Num>(x0: N, x1: N, f0: N, f1: N) -> Result<N, NumError> {
+    let slope = f1.clone().try_sub(f0)?;
+    let step = f1.try_mul(x1.clone().try_sub(x0)?)?.try_div(slope)?;
+    x1.try_sub(step)
+}
+
+pub(crate) fn contains<N: Num>(expr: &Expr<N>, x: &str) -> bool {
+    match expr {
+        Expr::Eq(lhs, rhs, _) | Expr::Op(_, lhs, rhs, _) => contains(lhs, x) || contains(rhs, x),
+        Expr::FuncDef(..) => false,
+        Expr::FuncOrVarMul(id, args, _) => id == x || args.iter().any(|arg| contains(arg, x)),
+        Expr::If(cond, then, otherwise) => {
+            contains(cond, x) || contains(then, x) || contains(otherwise, x)
+        }
+        Expr::Neg(expr, _) => contains(expr, x),
+        Expr::Num(_) => false,
+        Expr::Var(id, _) => id == x,
+    }
+}
+
+// Removes zero coefficients of the highest degrees
+fn poly_trim<N: Num>(mut poly: Vec<N>) -> Vec<N> {
+    while poly.last() == Some(&N::zero()) {
+        poly.pop();
+    }
+    poly
+}
+
+fn poly_add<N: Num>(mut lhs: Vec<N>, rhs: Vec<N>) -> Result<Vec<N>, NumError> {
+    if lhs.len() < rhs.len() {
+        lhs.resize(rhs.len(), N::zero());
+    }
+    for (l, r) in lhs.iter_mut().zip(rhs) {
+        *l = l.clone().try_add(r)?;
+    }
+    Ok(poly_trim(lhs))
+}
+
+fn poly_sub<N: Num>(lhs: Vec<N>, rhs: Vec<N>) -> Result<Vec<N>, NumError> {
+    poly_add(lhs, poly_neg(rhs)?)
+}
+
+fn poly_neg<N: Num>(poly: Vec<N>) -> Result<Vec<N>, NumError> {
+    poly.into_iter().map(N::try_neg).collect()
+}
+
+fn poly_mul<N: Num>(lhs: &[N], rhs: &[N]) -> Result<Vec<N>, NumError> {
+    if lhs.is_empty() || rhs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut result = vec![N::zero(); lhs.len() + rhs.len() - 1];
+    for (i, l) in lhs.iter().enumerate() {
+        for (j, r) in rhs.iter().enumerate() {
+            result[i + j] = result[i + j]
+                .clone()
+                .try_add(l.clone().try_mul(r.clone())?)?;
+        }
+    }
+    Ok(poly_trim(result))
+}
+
+fn poly_pow<N: Num>(poly: &[N], exponent: usize) -> Result<Vec<N>, NumError> {
+    let mut result = vec![N::one()];
+    for _ in 0..exponent {
+        result = poly_mul(&result, poly)?;
+    }
+    Ok(result)
+}
+
+fn poly_derive<N: Num>(poly: &[N]) -> Result<Vec<N>, NumError> {
+    poly.iter()
+        .enumerate()
+        .skip(1)
+        .map(|(i, c)| c.clone().try_mul(from_usize(i)))
+        .collect()
+}
+
+fn poly_eval<N: Num>(poly: &[N], x: &N) -> Result<N, NumError> {
+    // horner's method
+    poly.iter().rev().try_fold(N::zero(), |acc, c| {
+        acc.try_mul(x.clone())?.try_add(c.clone())
+    })
+}
+
+fn as_exponent<N: Num>(n: N) -> Option<usize> {
+    if !n.is_whole() || n < N::zero() {
+        return None;
+    }
+    let mut count = N::zero();
+    for exponent in 0..=MAX_DEGREE {
+        if count == n {
+            return Some(exponent);
+        }
+        count += N::one();
+    }
+    None
+}
+
+fn from_usize<N: Num>(n: usize) -> N {
+    let mut result = N::zero();
+    for _ in 0..n {
+        result += N::one();
+    }
+    result
+}
+
+fn from_i8<N: Num>(n: i8) -> Result<N, NumError> {
+    let magnitude = from_usize::<N>(usize::from(n.unsigned_abs()));
+    if n < 0 {
+        magnitude.try_neg()
+    } else {
+        Ok(magnitude)
+    }
+}
+
+fn abs<N: Num>(n: N) -> Result<N, NumError> {
+    if n < N::zero() {
+        n.try_neg()
+    } else {
+        Ok(n)
+    }
+}
+
+fn poly_is_root<N: Num>(poly: &[N], x: &N) -> Result<bool, NumError> {
+    // the terms are summed up without their signs, for the scale of the result
+    let magnitudes = poly
+        .iter()
+        .map(|c| abs(c.clone()))
+        .collect::<Result<Vec<N>, _>>()?;
+    is_root(
+        poly_eval(poly, x)?,
+        poly_eval(&magnitudes, &abs(x.clone())?)?,
+    )
+}
+
+// Whether fx is close enough to 0, relative to the scale of the values it was computed from
+fn is_root<N: Num>(fx: N, scale: N) -> Result<bool, NumError> {
+    if truncates::<N>() {
+        return Ok(fx == N::zero());
+    }
+    let scale = if scale < N::one() { N::one() } else { scale };
+    let tolerance = scale.try_div(from_usize::<N>(10).try_pow(from_usize(9))?)?;
+    Ok(abs(fx)? <= tolerance)
+}
+
+// Whether division rounds to whole numbers, like integer division does
+fn truncates<N: Num>() -> bool {
+    N::one().try_div(N::one() + N::one()) == Ok(N::zero())
+}
@@ -11,15 +11,52 @@ pub enum OpVal {
     Pow,
     Eq,
     Exclaim,
+    In, // unit conversion, spelled in or to
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    Equal,
+    NotEqual,
+    And,
+    Or,
 }
 use OpVal::*;
 
+impl OpVal {
+    // How the operator is written, for printing expressions
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Add => "+",
+            Sub => "-",
+            Mul => "*",
+            Div => "/",
+            Mod => "%",
+            Pow => "^",
+            Eq => "=",
+            Exclaim => "!",
+            In => "in",
+            Less => "<",
+            LessEq => "<=",
+            Greater => ">",
+            GreaterEq => ">=",
+            Equal => "==",
+            NotEqual => "!=",
+            And => "&&",
+            Or => "||",
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum SymbolVal {
     LP,
     RP,
     Comma,
     Pipe,
+    Degree,
+    Question,
+    Colon,
 }
 use SymbolVal::*;
 
@@ -42,6 +79,7 @@ pub struct Token<'input, N: Num> {
 pub enum TokenizeErrorCode<'input> {
     InvalidNumber(&'input str),
     UnrecognizedChar(char),
+    NoUnits(&'input str), // `in` or `to` after a value, for a Num type without units
 }
 use TokenizeErrorCode::*;
 
@@ -51,21 +89,24 @@ pub struct TokenizeError<'input> {
     pub span: Range<usize>,
 }
 
+// Exponents beyond this are invalid for Num types that need them expanded
+const MAX_EXPANDED_EXPONENT: i64 = 10_000;
+
 #[derive(Default, Debug, Clone)]
 pub struct TokenizeOptions {
     identifiers_contain_numbers: bool,
 }
 
-pub fn tokenize<N: Num>(input: &str) -> Result<Vec<Token<N>>, TokenizeError> {
+pub fn tokenize<N: Num>(input: &str) -> Result<Vec<Token<'_, N>>, TokenizeError<'_>> {
     tokenize_with_options(input, TokenizeOptions::default())
 }
 
 pub fn tokenize_with_options<N: Num>(
     input: &str,
     options: TokenizeOptions,
-) -> Result<Vec<Token<N>>, TokenizeError> {
+) -> Result<Vec<Token<'_, N>>, TokenizeError<'_>> {
     let mut tokens = Vec::with_capacity(16);
-    let mut chars = input.chars().enumerate().peekable();
+    let mut chars = input.char_indices().peekable();
 
     macro_rules! push_token {
         ($token:expr, $pos:expr, $len:expr) => {
@@ -79,6 +120,18 @@ pub fn tokenize_with_options<N: Num>(
         };
     }
 
+    // Pushes the two character token if `second` comes next, and the one character token otherwise
+    macro_rules! push_either {
+        ($cpos:expr, $second:literal, $two:expr, $one:expr) => {
+            if let Some((_, $second)) = chars.peek() {
+                chars.next(); // Consume the second character
+                push_token!($two, $cpos, 2)
+            } else {
+                push_token!($one, $cpos, 1)
+            }
+        };
+    }
+
     while let Some((cpos, c)) = chars.next() {
         match c {
             '+' => push_token!(Op(Add), cpos, 1),
@@ -87,54 +140,192 @@ pub fn tokenize_with_options<N: Num>(
             '/' => push_token!(Op(Div), cpos, 1),
             '%' => push_token!(Op(Mod), cpos, 1),
             '^' => push_token!(Op(Pow), cpos, 1),
-            '=' => push_token!(Op(Eq), cpos, 1),
-            '!' => push_token!(Op(Exclaim), cpos, 1),
+            '=' => push_either!(cpos, '=', Op(Equal), Op(Eq)),
+            '!' => push_either!(cpos, '=', Op(NotEqual), Op(Exclaim)),
+            '<' => push_either!(cpos, '=', Op(LessEq), Op(Less)),
+            '>' => push_either!(cpos, '=', Op(GreaterEq), Op(Greater)),
+            '&' => {
+                if let Some((_, '&')) = chars.next() {
+                    push_token!(Op(And), cpos, 2);
+                } else {
+                    return Err(TokenizeError {
+                        code: UnrecognizedChar(c),
+                        span: cpos..cpos + 1,
+                    });
+                }
+            }
 
             '(' => push_token!(Symbol(LP), cpos, 1),
             ')' => push_token!(Symbol(RP), cpos, 1),
             ',' => push_token!(Symbol(Comma), cpos, 1),
-            '|' => push_token!(Symbol(Pipe), cpos, 1),
+            // || is only an or between two operands, otherwise it is two pipes, like ||x| - 1|
+            '|' => match chars.peek() {
+                Some((_, '|')) if ends_operand(&tokens) && starts_operand(&input[cpos + 2..]) => {
+                    chars.next(); // Consume the second '|'
+                    push_token!(Op(Or), cpos, 2)
+                }
+                _ => push_token!(Symbol(Pipe), cpos, 1),
+            },
+            '°' => push_token!(Symbol(Degree), cpos, c.len_utf8()),
+            '?' => push_token!(Symbol(Question), cpos, 1),
+            ':' => push_token!(Symbol(Colon), cpos, 1),
+
+            // [lo, hi] is a single number if N parses it, like intervals do
+            '[' => {
+                let end = match input[cpos..].find(']') {
+                    Some(close) => cpos + close + 1,
+                    None => {
+                        return Err(TokenizeError {
+                            code: UnrecognizedChar(c),
+                            span: cpos..cpos + 1,
+                        })
+                    }
+                };
+                let text = &input[cpos..end];
+                if let Ok(num) = text.parse::<N>() {
+                    push_token!(Num(num), cpos, end - cpos);
+                } else {
+                    return Err(TokenizeError {
+                        code: InvalidNumber(text),
+                        span: cpos..end,
+                    });
+                }
+                while let Some((npos, _)) = chars.peek() {
+                    if *npos < end {
+                        chars.next(); // Consume the rest of the interval
+                    } else {
+                        break;
+                    }
+                }
+            }
 
             _ => {
-                if c.is_ascii_digit() || c == '.' {
+                if let Some((radix, end)) = scan_prefixed_integer(input, cpos) {
                     let start = cpos;
-                    let mut end = start + 1;
-                    while let Some((_, nc)) = chars.peek() {
-                        if nc.is_ascii_digit() || *nc == '.' {
-                            chars.next(); // Consume nc
-                            end += 1;
+                    while let Some((npos, _)) = chars.peek() {
+                        if *npos < end {
+                            chars.next(); // Consume the rest of the literal
                         } else {
                             break;
                         }
                     }
-                    if let Ok(num) = input[start..end].parse::<N>() {
+                    let text = &input[start..end];
+                    // Num types only parse decimal text, so go through u128
+                    let num = u128::from_str_radix(&text[2..].replace('_', ""), radix)
+                        .ok()
+                        .and_then(|n| n.to_string().parse::<N>().ok());
+                    if let Some(num) = num {
                         push_token!(Num(num), start, end - start);
                     } else {
                         return Err(TokenizeError {
-                            code: InvalidNumber(&input[start..end]),
+                            code: InvalidNumber(text),
                             span: start..end,
                         });
                     }
+                } else if c.is_ascii_digit() || c == '.' {
+                    let start = cpos;
+                    let mut end = scan_number(input, start);
+                    while let Some((npos, _)) = chars.peek() {
+                        if *npos < end {
+                            chars.next(); // Consume the rest of the number
+                        } else {
+                            break;
+                        }
+                    }
+                    let text = &input[start..end];
+                    let mut num = if text.contains('_') {
+                        text.replace('_', "").parse::<N>()
+                    } else {
+                        text.parse::<N>()
+                    };
+                    // Num types like integers do not parse exponents, so spell them out
+                    if num.is_err() {
+                        if let Some(expanded) = expand_exponent(&text.replace('_', "")) {
+                            num = expanded.parse::<N>();
+                        }
+                    }
+                    // 4i and 4j are imaginary if N parses them, otherwise 4 times the identifier
+                    if let Some(suffix @ ('i' | 'j')) = input[end..].chars().next() {
+                        let rest = &input[end + 1..];
+                        if !rest.starts_with(|nc: char| nc == '_' || nc.is_alphanumeric()) {
+                            if let Ok(imaginary) =
+                                format!("{}{}", text.replace('_', ""), suffix).parse::<N>()
+                            {
+                                num = Ok(imaginary);
+                                end += 1;
+                                chars.next(); // Consume the suffix
+                            }
+                        }
+                    }
+                    // 5 ± 0.1 is a single number if N parses it, like intervals do, but 4i ± 1 is not
+                    let error = scan_error(input, end).filter(|_| end == start + text.len());
+                    if let Some(error) = error {
+                        let uncertain = format!(
+                            "{}±{}",
+                            text.replace('_', ""),
+                            input[error.clone()].replace('_', "")
+                        );
+                        if let Ok(uncertain) = uncertain.parse::<N>() {
+                            num = Ok(uncertain);
+                            end = error.end;
+                            while let Some((npos, _)) = chars.peek() {
+                                if *npos < end {
+                                    chars.next(); // Consume the error
+                                } else {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    if let Ok(num) = num {
+                        push_token!(Num(num), start, end - start);
+                    } else {
+                        return Err(TokenizeError {
+                            code: InvalidNumber(text),
+                            span: start..end,
+                        });
+                    }
+                    // 90deg is the same as 90°
+                    if let Some(rest) = input[end..].strip_prefix("deg") {
+                        if !rest.starts_with(|nc: char| nc == '_' || nc.is_alphanumeric()) {
+                            push_token!(Symbol(Degree), end, 3);
+                            for _ in 0..3 {
+                                chars.next(); // Consume 'deg'
+                            }
+                        }
+                    }
                 } else if c == '_' || c.is_alphabetic() {
                     let start = cpos;
-                    let mut end = start + 1;
-                    while let Some((_, nc)) = chars.peek() {
+                    let mut end = start + c.len_utf8();
+                    while let Some((npos, nc)) = chars.peek() {
                         // If it is any of _ A-z (or digits if option)
                         if *nc == '_'
                             || nc.is_alphanumeric()
                             || (options.identifiers_contain_numbers && nc.is_ascii_digit())
                         {
+                            end = npos + nc.len_utf8();
                             chars.next(); // Consume next character
-                            end += 1;
                         } else {
                             break;
                         }
                     }
-                    push_token!(Id(&input[start..end]), start, end - start);
+                    match &input[start..end] {
+                        // only after a value, so they can still be variable names elsewhere
+                        "in" | "to" if ends_operand(&tokens) => {
+                            if !N::has_units() {
+                                return Err(TokenizeError {
+                                    code: NoUnits(&input[start..end]),
+                                    span: start..end,
+                                });
+                            }
+                            push_token!(Op(In), start, end - start)
+                        }
+                        id => push_token!(Id(id), start, end - start),
+                    }
                 } else if !c.is_whitespace() {
                     return Err(TokenizeError {
                         code: UnrecognizedChar(c),
-                        span: cpos..cpos + 1,
+                        span: cpos..cpos + c.len_utf8(),
                     });
                 }
             }
@@ -142,3 +333,111 @@ pub fn tokenize_with_options<N: Num>(
     }
     Ok(tokens)
 }
+
+// Whether the last token ends an operand, so that an operator can follow it
+fn ends_operand<N: Num>(tokens: &[Token<'_, N>]) -> bool {
+    match tokens.last().map(|token| &token.value) {
+        Some(Num(_) | Id(_) | Symbol(RP | Degree) | Op(Exclaim)) => true,
+        // the pipe closes an absolute value if it is the second of a pair
+        Some(Symbol(Pipe)) => {
+            let pipes = tokens
+                .iter()
+                .filter(|token| token.value == Symbol(Pipe))
+                .count();
+            pipes % 2 == 0
+        }
+        _ => false,
+    }
+}
+
+// Whether the text, after any whitespace, begins with something that starts an operand
+fn starts_operand(rest: &str) -> bool {
+    rest.trim_start().starts_with(|c: char| {
+        c.is_alphanumeric() || matches!(c, '_' | '.' | '(' | '|' | '[' | '-')
+    })
+}
+
+// Returns the radix and end of a 0x, 0b or 0o prefixed integer starting at `start`, if any
+fn scan_prefixed_integer(input: &str, start: usize) -> Option<(u32, usize)> {
+    let bytes = input.as_bytes();
+    let radix = match (bytes.get(start), bytes.get(start + 1)) {
+        (Some(b'0'), Some(b'x' | b'X')) => 16,
+        (Some(b'0'), Some(b'b' | b'B')) => 2,
+        (Some(b'0'), Some(b'o' | b'O')) => 8,
+        _ => return None,
+    };
+    let is_digit_at = |i: usize| bytes.get(i).is_some_and(|b| (*b as char).is_digit(radix));
+
+    // 0b without any binary digits after it is 0 followed by the identifier b
+    if !is_digit_at(start + 2) {
+        return None;
+    }
+    let mut end = start + 3;
+    while is_digit_at(end) || (bytes.get(end) == Some(&b'_') && is_digit_at(end + 1)) {
+        end += 1;
+    }
+    Some((radix, end))
+}
+
+// Returns the end of the number starting at `start`, such as 12.5, 1_000 or 6.022e23
+fn scan_number(input: &str, start: usize) -> usize {
+    let bytes = input.as_bytes();
+    let is_digit_at = |i: usize| bytes.get(i).is_some_and(u8::is_ascii_digit);
+
+    let mut end = start + 1;
+    while let Some(b) = bytes.get(end) {
+        match b {
+            b'0'..=b'9' | b'.' => end += 1,
+            // separators must sit between two digits
+            b'_' if is_digit_at(end - 1) && is_digit_at(end + 1) => end += 1,
+            _ => break,
+        }
+    }
+
+    // An exponent only counts if digits follow the 'e' and its optional sign,
+    // so 2e is still 2 followed by the identifier e
+    if let Some(b'e' | b'E') = bytes.get(end) {
+        let digits = match bytes.get(end + 1) {
+            Some(b'+' | b'-') => end + 2,
+            _ => end + 1,
+        };
+        if is_digit_at(digits) {
+            end = digits + 1;
+            while is_digit_at(end) || (bytes.get(end) == Some(&b'_') && is_digit_at(end + 1)) {
+                end += 1;
+            }
+        }
+    }
+    end
+}
+
+// Returns where the number after a ± following `end` is, such as 0.1 in 5 ± 0.1
+fn scan_error(input: &str, end: usize) -> Option<Range<usize>> {
+    let error = input[end..].trim_start().strip_prefix('±')?.trim_start();
+    if !error.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let start = input.len() - error.len();
+    Some(start..scan_number(input, start))
+}
+
+// Writes a whole number like 1.5e3 without its exponent as 1500, or None if it is not whole
+fn expand_exponent(text: &str) -> Option<String> {
+    let (mantissa, exponent) = text.split_once(['e', 'E'])?;
+    let exponent: i64 = exponent.parse().ok()?;
+    if exponent.abs() > MAX_EXPANDED_EXPONENT {
+        return None;
+    }
+    let (int, fract) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int, fract);
+    // where the decimal point ends up within the digits
+    let point = usize::try_from(int.len() as i64 + exponent).ok()?;
+    if point >= digits.len() {
+        Some(format!("{}{}", digits, "0".repeat(point - digits.len())))
+    } else if digits[point..].bytes().all(|b| b == b'0') {
+        // .0e0 keeps one zero
+        Some(digits[..point.max(1)].to_string())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,41 @@
+use rscalc::{parse, tokenize, AngleMode, Interpreter};
+
+fn eval(input: &str, mode: AngleMode) -> f64 {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    let mut interpreter = Interpreter::default();
+    interpreter.angle_mode = mode;
+    interpreter.eval(&expr).unwrap()
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-12,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn trigonometry_takes_and_returns_the_angle_mode() {
+    assert_close(eval("sin(30)", AngleMode::Degrees), 0.5);
+    assert_close(eval("cos(200)", AngleMode::Gradians), -1.0);
+    assert_close(eval("sin(pi / 6)", AngleMode::Radians), 0.5);
+    assert_close(eval("asin(1)", AngleMode::Degrees), 90.0);
+    assert_close(eval("atan2(1, 1)", AngleMode::Gradians), 50.0);
+}
+
+#[test]
+fn degree_literals_are_converted_to_the_angle_mode() {
+    assert_close(eval("90°", AngleMode::Radians), std::f64::consts::FRAC_PI_2);
+    assert_close(eval("90deg", AngleMode::Gradians), 100.0);
+    assert_close(eval("sin(30°)", AngleMode::Radians), 0.5);
+    assert_close(eval("sin(30°)", AngleMode::Gradians), 0.5);
+}
+
+#[test]
+fn other_functions_ignore_the_angle_mode() {
+    assert_close(eval("sqrt(16) + exp(0)", AngleMode::Degrees), 5.0);
+    assert_close(eval("sinh(0)", AngleMode::Degrees), 0.0);
+}
@@ -0,0 +1,76 @@
+use rscalc::{parse, tokenize, InterpretError, Interpreter};
+
+fn eval(input: &str) -> Result<f64, InterpretError<'static>> {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    Interpreter::default()
+        .eval(&expr)
+        .map_err(InterpretError::into_owned)
+}
+
+fn assert_close(input: &str, expected: f64) {
+    let actual = eval(input).unwrap();
+    assert!(
+        (actual - expected).abs() < 1e-12 * expected.abs().max(1.0),
+        "{}: {} != {}",
+        input,
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn every_group_of_builtins_is_registered() {
+    assert_close("pi", std::f64::consts::PI);
+    assert_close(
+        "abs(-2) + cbrt(27) + floor(2.5) + ceil(2.5) + trunc(-2.5)",
+        8.0,
+    );
+    assert_close("round(2.5) + round(-2.5) + sign(-4)", -1.0);
+    assert_close("sin(pi / 2) + cos(0) + tan(0)", 2.0);
+    assert_close(
+        "asin(1) + acos(1) + atan(1)",
+        3.0 * std::f64::consts::FRAC_PI_4,
+    );
+    assert_close("atan2(1, -1)", 3.0 * std::f64::consts::FRAC_PI_4);
+    assert_close("sec(0) + csc(pi / 2) + cot(pi / 4)", 3.0);
+    assert_close(
+        "tanh(asinh(0.5) - acosh(1)) + atanh(0)",
+        0.5f64.asinh().tanh(),
+    );
+    assert_close("ln(e) + log(1000) + log(8, 2) + log2(4) + log10(0.1)", 8.0);
+    assert_close("exp(2)", 2f64.exp());
+}
+
+#[test]
+fn variadic_builtins_take_any_number_of_arguments() {
+    assert_close("min(3, -1, 2)", -1.0);
+    assert_close("max(3)", 3.0);
+    assert_close("sum(1, 2, 3, 4)", 10.0);
+    assert_close("hypot(3, 4, 12)", 13.0);
+}
+
+#[test]
+fn arity_and_domain_are_checked() {
+    assert_eq!(
+        eval("sin()"),
+        Err(InterpretError::TooFewArgs("sin".into(), 1))
+    );
+    assert_eq!(
+        eval("atan2(1)"),
+        Err(InterpretError::TooFewArgs("atan2".into(), 2))
+    );
+    assert_eq!(
+        eval("log(1, 2, 3)"),
+        Err(InterpretError::TooManyArgs("log".into(), 2))
+    );
+    assert_eq!(
+        eval("min()"),
+        Err(InterpretError::TooFewArgs("min".into(), 1))
+    );
+    assert_eq!(eval("ln(0)"), Err(InterpretError::DomainError("ln".into())));
+    assert_eq!(
+        eval("log(8, 1)"),
+        Err(InterpretError::DomainError("log".into()))
+    );
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "executable")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_rscalc"))
+        .args(args)
+        .output()
+        .unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn run_repl(input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rscalc"))
+        .arg("--no-color")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap()
+}
+
+#[test]
+fn radix_prints_whole_results_in_another_base() {
+    assert_eq!(run(&["-r", "hex", "255"]), "255 (0xff)\n");
+    assert_eq!(run(&["--radix", "bin", "--", "-5"]), "-5 (-0b101)\n");
+    assert_eq!(run(&["-b", "i64", "-r", "oct", "0x1ff"]), "511 (0o777)\n");
+    // fractions have no hex form
+    assert_eq!(run(&["-r", "hex", "2.5"]), "2.5\n");
+}
+
+#[test]
+fn radix_command_switches_the_base_in_the_repl() {
+    let output = run_repl("radix hex\n255\nradix off\n255\n");
+    assert!(output.contains(": 255 (0xff)\n"), "{}", output);
+    assert!(output.ends_with(": 255\n"), "{}", output);
+}
+
+#[test]
+fn angle_mode_is_set_by_flag_or_command() {
+    assert_eq!(run(&["-a", "deg", "asin(1)"]), "90\n");
+    let output = run_repl("angle grad\nangle\n");
+    assert!(output.ends_with("\nGradians\n"), "{}", output);
+}
+
+#[test]
+fn decimal_results_leave_out_trailing_zeros() {
+    assert_eq!(run(&["-b", "decimal", "2^-1"]), "0.5\n");
+    assert_eq!(run(&["-b", "decimal", "1.50 + 1"]), "2.5\n");
+}
@@ -0,0 +1,18 @@
+use rscalc::{ensure_arg_count, parse, tokenize, Interpreter, Variant};
+
+#[test]
+fn closures_keep_their_captured_state() {
+    let rate = 0.2;
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.set_var(
+        String::from("tax"),
+        Variant::closure(move |id, args| {
+            ensure_arg_count(1, 1, args.len(), id)?;
+            Ok(args[0] * rate)
+        }),
+    );
+    let tokens = tokenize("2 * tax(50) + 1").unwrap();
+    let expr = parse(&tokens).unwrap();
+    assert_eq!(interpreter.clone().eval(&expr).unwrap(), 21.0);
+    assert_eq!(interpreter.eval(&expr).unwrap(), 21.0);
+}
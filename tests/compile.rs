@@ -0,0 +1,110 @@
+use rscalc::{parse, tokenize, Expr, InterpretError, Interpreter, Variant};
+
+fn parse_str(input: &str) -> Expr<f64> {
+    let tokens = tokenize(input).unwrap();
+    parse(&tokens).unwrap()
+}
+
+fn define(interpreter: &mut Interpreter<f64>, input: &str) {
+    interpreter.eval(&parse_str(input)).unwrap();
+}
+
+#[test]
+fn compiled_expressions_evaluate_like_the_interpreter() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.set_var(String::from("k"), Variant::Num(0.5));
+    define(&mut interpreter, "f(x, z) = x^2 + z * y");
+    define(&mut interpreter, "g(y) = f(y, 2) - y");
+    for input in [
+        "3x^3 - 2x + 1",
+        "sin(x) * cos(y) + k",
+        "x < y ? x : y - 1",
+        "if(x > 0 && y > 0, sqrt(x * y), -1)",
+        "x(y + 1) + |x - y|",
+        "x > 1 || y > 1",
+        "g(x) + f(x, y)",
+        "3! + x % 2",
+    ] {
+        let expr = parse_str(input);
+        let compiled = interpreter.compile(&expr, &["x", "y"]).unwrap();
+        for (x, y) in [(-1.5, 2.0), (0.5, 0.25), (3.0, 4.0)] {
+            interpreter.set_var(String::from("x"), Variant::Num(x));
+            interpreter.set_var(String::from("y"), Variant::Num(y));
+            let expected = interpreter.eval(&expr).unwrap();
+            assert_eq!(
+                compiled.eval(&[x, y]).unwrap(),
+                expected,
+                "{} at {}, {}",
+                input,
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[test]
+fn compiled_expressions_only_evaluate_what_they_use() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.strict = true;
+    let expr = parse_str("x > 0 && ln(x) > 1 ? 1 / ln(x) : 0");
+    let compiled = interpreter.compile(&expr, &["x"]).unwrap();
+    assert_eq!(compiled.eval(&[-1.0]).unwrap(), 0.0);
+    assert_eq!(compiled.eval(&[1.0]).unwrap(), 0.0);
+    assert_eq!(compiled.eval(&[std::f64::consts::E.powi(2)]).unwrap(), 0.5);
+}
+
+#[test]
+fn eval_with_reuses_a_buffer_sized_when_compiling() {
+    let mut interpreter = Interpreter::<f64>::default();
+    define(&mut interpreter, "h(a, b) = a * b");
+    // the locals of inlined functions and the deepest the stack gets, where only one branch counts
+    for (input, size) in [
+        ("x + (x + (x + (x + (x + 1))))", 6),
+        ("h(x, x + 1) + x", 5),
+        ("x < 0 ? -x : x > 1 ? 1 : x", 2),
+    ] {
+        let expr = parse_str(input);
+        let compiled = interpreter.compile(&expr, &["x"]).unwrap();
+        let mut scratch = Vec::new();
+        for x in [-2.0, 0.5, 3.0] {
+            assert_eq!(compiled.eval_with(&[x], &mut scratch), compiled.eval(&[x]));
+            assert_eq!(scratch.capacity(), size, "{}", input);
+        }
+    }
+}
+
+#[test]
+fn compile_errors() {
+    let mut interpreter = Interpreter::<f64>::default();
+    define(&mut interpreter, "fact(n) = n < 1 ? 1 : n * fact(n - 1)");
+    let compile = |input: &str| {
+        interpreter
+            .compile(&parse_str(input), &["x"])
+            .map(|_| ())
+            .map_err(InterpretError::into_owned)
+    };
+    assert_eq!(
+        compile("fact(x)"),
+        Err(InterpretError::NotCompilable("fact".into()))
+    );
+    assert_eq!(
+        compile("sigma(i, 1, x, i)"),
+        Err(InterpretError::NotCompilable("sigma".into()))
+    );
+    assert_eq!(
+        compile("y = x"),
+        Err(InterpretError::NotCompilable("=".into()))
+    );
+    assert_eq!(
+        compile("x + q"),
+        Err(InterpretError::VarDoesNotExist("q".into()))
+    );
+
+    let compiled = interpreter.compile(&parse_str("1 / x"), &["x"]).unwrap();
+    assert_eq!(compiled.eval(&[0.0]), Err(InterpretError::DivisionByZero));
+    assert_eq!(
+        compiled.eval(&[]),
+        Err(InterpretError::TooFewArgs("eval".into(), 1))
+    );
+}
@@ -0,0 +1,37 @@
+use rscalc::{parse, tokenize, Complex, InterpretError, Interpreter};
+
+fn eval(input: &str) -> Result<Complex, InterpretError<'static>> {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    Interpreter::default()
+        .eval(&expr)
+        .map_err(InterpretError::into_owned)
+}
+
+#[test]
+fn imaginary_literals_and_builtins() {
+    assert_eq!(eval("(3 + 4i) * 2j"), Ok(Complex::new(-8.0, 6.0)));
+    assert_eq!(eval("sqrt(-4)"), Ok(Complex::new(0.0, 2.0)));
+    assert_eq!(eval("abs(3 + 4i)"), Ok(Complex::new(5.0, 0.0)));
+    assert_eq!(
+        eval("conj(1 + 2i) + re(5i) + im(5i)"),
+        Ok(Complex::new(6.0, -2.0))
+    );
+}
+
+#[test]
+fn factorials_past_170_overflow_immediately() {
+    assert_eq!(eval("5!"), Ok(Complex::new(120.0, 0.0)));
+    assert_eq!(
+        eval("factorial(171)"),
+        Err(InterpretError::Overflow("factorial".into()))
+    );
+    assert_eq!(
+        eval("factorial(1e12)"),
+        Err(InterpretError::Overflow("factorial".into()))
+    );
+    assert_eq!(
+        eval("factorial(2i)"),
+        Err(InterpretError::DomainError("factorial".into()))
+    );
+}
@@ -0,0 +1,46 @@
+use rscalc::{parse, tokenize, Interpreter, ParseErrorCode};
+
+fn eval(input: &str) -> f64 {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    Interpreter::default().eval(&expr).unwrap()
+}
+
+#[test]
+fn conditional_binds_looser_than_logic_and_is_right_associative() {
+    assert_eq!(eval("1 < 2 || 0 ? 10 : 20"), 10.0);
+    assert_eq!(eval("0 ? 1 : 0 ? 2 : 3"), 3.0);
+    assert_eq!(eval("1 ? 0 ? 1 : 2 : 3"), 2.0);
+    assert_eq!(eval("x = 0 ? 1 : 2"), 2.0);
+    assert_eq!(eval("2 * (1 ? 3 : 4) + 1"), 7.0);
+}
+
+#[test]
+fn conditional_only_evaluates_the_chosen_branch() {
+    assert_eq!(eval("1 ? 5 : 1 / 0"), 5.0);
+    assert_eq!(eval("0 ? undefined : 5"), 5.0);
+}
+
+#[test]
+fn if_is_parsed_like_the_conditional_operator() {
+    let tokens = tokenize::<f64>("if(x, 1, 2)").unwrap();
+    let expected = parse(&tokenize::<f64>("x ? 1 : 2").unwrap()).unwrap();
+    assert_eq!(parse(&tokens).unwrap(), expected);
+    assert_eq!(eval("if(1, 5, 1 / 0)"), 5.0);
+    assert_eq!(eval("2 * if(0, 3, 4)"), 8.0);
+}
+
+#[test]
+fn if_needs_three_arguments() {
+    let tokens = tokenize::<f64>("1 + if(1, 2)").unwrap();
+    let err = parse(&tokens).unwrap_err();
+    assert_eq!(err.code, ParseErrorCode::ExpectedArgs("if", 3));
+    assert_eq!(err.span, 4..12);
+    assert_eq!(
+        Interpreter::<f64>::default()
+            .eval_str("if()")
+            .unwrap_err()
+            .to_string(),
+        "Function \"if\" takes exactly 3 arguments. (at 0..4)"
+    );
+}
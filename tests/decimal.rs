@@ -0,0 +1,55 @@
+#![cfg(feature = "decimal")]
+
+use rscalc::{parse, tokenize, AngleMode, Decimal, InterpretError, Interpreter};
+use std::str::FromStr;
+
+fn eval(input: &str, mode: AngleMode) -> Result<Decimal, InterpretError<'static>> {
+    let mut interpreter = Interpreter::default();
+    interpreter.angle_mode = mode;
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    interpreter.eval(&expr).map_err(InterpretError::into_owned)
+}
+
+fn decimal(text: &str) -> Decimal {
+    Decimal::from_str(text).unwrap()
+}
+
+#[test]
+fn decimal_fractions_are_exact() {
+    assert_eq!(eval("0.1 + 0.2", AngleMode::Radians), Ok(decimal("0.3")));
+    assert_eq!(eval("1.10 * 3", AngleMode::Radians), Ok(decimal("3.3")));
+    assert_eq!(eval("2^-1", AngleMode::Radians), Ok(decimal("0.5")));
+}
+
+#[test]
+fn angles_too_large_to_convert_overflow() {
+    let max = "79228162514264337593543950335";
+    assert_eq!(
+        eval(&format!("sin({})", max), AngleMode::Degrees),
+        Err(InterpretError::Overflow("sin".into()))
+    );
+    assert_eq!(
+        eval(&format!("{}°", max), AngleMode::Radians),
+        Err(InterpretError::Overflow("deg".into()))
+    );
+    assert!(eval(&format!("sin({})", max), AngleMode::Radians).is_ok());
+}
+
+#[test]
+fn logarithms_of_bases_close_to_1_are_errors() {
+    assert_eq!(
+        eval(
+            "log(10, 1.0000000000000000000000000001)",
+            AngleMode::Radians
+        ),
+        Err(InterpretError::DomainError("log".into()))
+    );
+    assert_eq!(
+        eval(
+            "log(79228162514264337593543950335, 1.0000000000000000000000000002)",
+            AngleMode::Radians
+        ),
+        Err(InterpretError::Overflow("log".into()))
+    );
+}
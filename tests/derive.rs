@@ -0,0 +1,69 @@
+use rscalc::{derive, parse, tokenize, Expr, InterpretError, Interpreter, Variant};
+
+fn parse_str(input: &str) -> Expr<f64> {
+    let tokens = tokenize(input).unwrap();
+    parse(&tokens).unwrap()
+}
+
+// Compares the derivative at x with a central difference
+fn assert_derivative(input: &str, x: f64) {
+    let expr = parse_str(input);
+    let derivative = derive(&expr, "x").unwrap();
+    let mut interpreter = Interpreter::default();
+    let mut at = |expr: &Expr<f64>, x: f64| {
+        interpreter.set_var(String::from("x"), Variant::Num(x));
+        interpreter.eval(expr).unwrap()
+    };
+    let h = 1e-6;
+    let expected = (at(&expr, x + h) - at(&expr, x - h)) / (2.0 * h);
+    let actual = at(&derivative, x);
+    assert!(
+        (actual - expected).abs() < 1e-6 * expected.abs().max(1.0),
+        "d/dx {} at {}: {} != {}",
+        input,
+        x,
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn derivatives_match_finite_differences() {
+    for input in [
+        "3x^3 - 2x + 1",
+        "x * sin(x)",
+        "sqrt(x) / (1 + x)",
+        "x^x",
+        "2^x",
+        "e^(2 * x)",
+        "-cos(x^2)",
+        "ln(x) * atan(x)",
+        "log(x, 2) + log10(x)",
+        "|x - 3|",
+        "x < 1 ? x^2 : 2x",
+        "tanh(x) + asinh(x)",
+        "x(x + 1)",
+    ] {
+        assert_derivative(input, 0.7);
+        assert_derivative(input, 2.3);
+    }
+}
+
+#[test]
+fn derivative_leaves_out_zeros_and_ones() {
+    assert_eq!(derive(&parse_str("x^2"), "x").unwrap(), parse_str("2 * x"));
+    assert_eq!(derive(&parse_str("y * x"), "x").unwrap(), parse_str("y"));
+    assert_eq!(derive(&parse_str("y^2"), "x").unwrap(), parse_str("0"));
+}
+
+#[test]
+fn unknown_functions_are_not_differentiable() {
+    assert_eq!(
+        derive(&parse_str("f(x)"), "x").unwrap_err(),
+        InterpretError::NotDifferentiable("f".into())
+    );
+    assert_eq!(
+        derive(&parse_str("x % x"), "x").unwrap_err(),
+        InterpretError::NotDifferentiable("%".into())
+    );
+}
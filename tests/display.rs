@@ -0,0 +1,72 @@
+use rscalc::{parse, tokenize, Expr, Num, Quantity};
+use std::fmt::Display;
+
+fn parse_str(input: &str) -> Expr<f64> {
+    let tokens = tokenize(input).unwrap();
+    parse(&tokens).unwrap()
+}
+
+fn assert_round_trips<N: Num + Display>(input: &str) {
+    let parse_as = |input: &str| {
+        let tokens = tokenize::<N>(input).unwrap();
+        parse(&tokens).unwrap()
+    };
+    let expr = parse_as(input);
+    let display = expr.to_string();
+    assert_eq!(
+        parse_as(&display),
+        expr,
+        "{} displayed as {}",
+        input,
+        display
+    );
+}
+
+#[test]
+fn display_round_trips_through_parse() {
+    for input in [
+        "2^3^2",
+        "(2^3)^2",
+        "-x^2",
+        "(-x)^2",
+        "2^-x",
+        "a - (b - c) - d",
+        "a / (b * c) % d",
+        "-(x + 1) * 3",
+        "x - -y",
+        "a < b == c < d",
+        "a < (b == c)",
+        "x || y && z",
+        "(x || y) && z",
+        "a ? b : c ? d : e",
+        "(a ? b : c) ? d : e",
+        "a ? b = c : d",
+        "x = y ? 1 : 2",
+        "f(x, y) = x^2 + sqrt(y, 3)",
+        "|x - 1| + 3! + 90°",
+        "x(y + 1) - sigma(i, 1, 10, i^2)",
+    ] {
+        assert_round_trips::<f64>(input);
+    }
+    // only types with units have conversions
+    for input in ["5 m^2 in ft^2", "(1 km + 1 m in ft) + 1"] {
+        assert_round_trips::<Quantity>(input);
+    }
+}
+
+#[test]
+fn display_only_adds_needed_parentheses() {
+    assert_eq!(parse_str("((a + b)) * (c)").to_string(), "(a + b) * c");
+    assert_eq!(parse_str("a + (b * c)").to_string(), "a + b * c");
+    assert_eq!(
+        parse_str("(a - b) - (c - d)").to_string(),
+        "a - b - (c - d)"
+    );
+    assert_eq!(parse_str("2^(3^2)").to_string(), "2^3^2");
+    assert_eq!(parse_str("-(x^2)").to_string(), "-x^2");
+    assert_eq!(parse_str("2x").to_string(), "2 * x");
+    assert_eq!(
+        parse_str("f(x)=x<0?-x:x").to_string(),
+        "f(x) = x < 0 ? -x : x"
+    );
+}
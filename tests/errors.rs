@@ -0,0 +1,57 @@
+use rscalc::{parse, tokenize, ErrorKind, InterpretError, Interpreter};
+
+fn eval_strict(input: &str) -> Result<f64, InterpretError<'static>> {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    let mut interpreter = Interpreter::default();
+    interpreter.strict = true;
+    interpreter.eval(&expr).map_err(InterpretError::into_owned)
+}
+
+#[test]
+fn invalid_operations_are_errors() {
+    assert_eq!(eval_strict("1 / 0"), Err(InterpretError::DivisionByZero));
+    assert_eq!(eval_strict("0 % 0"), Err(InterpretError::DivisionByZero));
+    assert_eq!(
+        eval_strict("sqrt(-1)"),
+        Err(InterpretError::DomainError("sqrt".into()))
+    );
+    assert_eq!(
+        eval_strict("factorial(2.5)"),
+        Err(InterpretError::DomainError("factorial".into()))
+    );
+}
+
+#[test]
+fn factorials_past_170_overflow_immediately() {
+    assert_eq!(eval_strict("factorial(170)").map(f64::is_finite), Ok(true));
+    assert_eq!(
+        eval_strict("factorial(171)"),
+        Err(InterpretError::Overflow("factorial".into()))
+    );
+    // this would take hours to multiply out
+    assert_eq!(
+        eval_strict("factorial(1e12)"),
+        Err(InterpretError::Overflow("factorial".into()))
+    );
+}
+
+#[test]
+fn errors_point_at_the_call_or_operator_that_failed() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.strict = true;
+    let span = |input| interpreter.clone().eval_str(input).unwrap_err().span;
+    assert_eq!(span("1 + sqrt(-1)"), 4..12);
+    assert_eq!(span("2 * (1 / 0)"), 7..8);
+    assert_eq!(span("1 + y"), 4..5);
+    assert_eq!(span("|sqrt(-1)| + 1"), 1..9);
+    assert_eq!(span("1 +"), 3..4);
+
+    interpreter.eval_str("f(x) = sqrt(x)").unwrap();
+    let err = interpreter.eval_str("2 * f(-1)").unwrap_err();
+    assert_eq!(err.span, 4..9);
+    assert_eq!(
+        err.kind,
+        ErrorKind::Interpret(InterpretError::DomainError("sqrt".into()))
+    );
+}
@@ -0,0 +1,43 @@
+use rscalc::{parse, tokenize, InterpretError, Interpreter};
+
+fn eval_all(inputs: &[&str]) -> Result<f64, InterpretError<'static>> {
+    let mut interpreter = Interpreter::default();
+    let mut result = Ok(0.0);
+    for input in inputs {
+        let tokens = tokenize(input).unwrap();
+        let expr = parse(&tokens).unwrap();
+        result = interpreter.eval(&expr).map_err(InterpretError::into_owned);
+    }
+    result
+}
+
+#[test]
+fn user_functions_bind_their_arguments() {
+    assert_eq!(eval_all(&["f(x, y) = x^2 + y", "f(3, 1)"]), Ok(10.0));
+    // parameters shadow variables only during the call
+    assert_eq!(eval_all(&["x = 5", "f(x) = x * 2", "f(1) + x"]), Ok(7.0));
+    assert_eq!(
+        eval_all(&["a = 3", "f(x) = x + a", "a = 10", "f(1)"]),
+        Ok(11.0)
+    );
+}
+
+#[test]
+fn user_functions_check_their_argument_count() {
+    assert_eq!(
+        eval_all(&["f(x, y) = x^2 + y", "f(1)"]),
+        Err(InterpretError::TooFewArgs("f".into(), 2))
+    );
+    assert_eq!(
+        eval_all(&["f(x, y) = x^2 + y", "f(1, 2, 3)"]),
+        Err(InterpretError::TooManyArgs("f".into(), 2))
+    );
+}
+
+#[test]
+fn runaway_recursion_is_an_error() {
+    assert_eq!(
+        eval_all(&["f(x) = f(x + 1)", "f(1)"]),
+        Err(InterpretError::RecursionLimit("f".into()))
+    );
+}
@@ -0,0 +1,93 @@
+use rscalc::{parse, tokenize, InterpretError, Interpreter, Num, Unsigned};
+
+fn eval<N: Num>(input: &str) -> Result<N, InterpretError<'static>>
+where
+    Interpreter<N>: Default,
+{
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    Interpreter::default()
+        .eval(&expr)
+        .map_err(InterpretError::into_owned)
+}
+
+fn overflow<N: Num>(id: &str) -> Result<N, InterpretError<'static>> {
+    Err(InterpretError::Overflow(id.to_string().into()))
+}
+
+#[test]
+fn division_truncates_and_exponents_are_whole() {
+    assert_eq!(eval::<i64>("7 / 2"), Ok(3));
+    assert_eq!(eval::<i64>("-7 % 3"), Ok(-1));
+    assert_eq!(eval::<i128>("2^100"), Ok(1 << 100));
+    assert_eq!(
+        eval::<i64>("2^-1"),
+        Err(InterpretError::DomainError("^".into()))
+    );
+    assert_eq!(eval::<i64>("1 / 0"), Err(InterpretError::DivisionByZero));
+}
+
+#[test]
+fn overflow_is_an_error_instead_of_a_panic() {
+    assert_eq!(eval::<i64>("2^63"), overflow("^"));
+    assert_eq!(eval::<i64>("9223372036854775807 + 1"), overflow("+"));
+    assert_eq!(eval::<i64>("-9223372036854775807 - 2"), overflow("-"));
+    assert_eq!(eval::<i128>("2^64 * 2^64"), overflow("*"));
+    assert_eq!(eval::<i64>("21!"), overflow("factorial"));
+    // x(4) multiplies like x * 4 does
+    assert_eq!(
+        eval::<i64>("(x = 4611686018427387904) + x(4)"),
+        overflow("*")
+    );
+}
+
+#[test]
+fn unsigned_underflow_is_an_error() {
+    assert_eq!(eval::<Unsigned>("0 - 1"), overflow("-"));
+    assert_eq!(
+        eval::<Unsigned>("-1"),
+        Err(InterpretError::DomainError("-".into()))
+    );
+    assert_eq!(eval::<Unsigned>("-0"), Ok(Unsigned(0)));
+    assert_eq!(eval::<Unsigned>("abs(3) - 5"), overflow("-"));
+    assert_eq!(
+        eval::<Unsigned>("18446744073709551615"),
+        Ok(Unsigned(u64::MAX))
+    );
+}
+
+#[test]
+fn equations_without_negative_numbers_are_solved() {
+    assert_eq!(eval::<Unsigned>("2*x + 3 = 11"), Ok(Unsigned(4)));
+    assert_eq!(eval::<Unsigned>("11 = 2x + 3"), Ok(Unsigned(4)));
+    assert_eq!(eval::<Unsigned>("x^2 = 9"), Ok(Unsigned(3)));
+    assert_eq!(eval::<Unsigned>("x^3 + x = 30"), Ok(Unsigned(3)));
+    assert_eq!(
+        eval::<Unsigned>("x + 5 = 2"),
+        Err(InterpretError::NoSolution("x".into()))
+    );
+    assert_eq!(
+        eval::<Unsigned>("2x = 3"),
+        Err(InterpretError::NoSolution("x".into()))
+    );
+}
+
+#[cfg(feature = "bigint")]
+#[test]
+fn bigint_grows_until_its_limits() {
+    use rscalc::BigInt;
+
+    assert_eq!(
+        eval::<BigInt>("2^100 * 2^100").map(|n| n.to_string()),
+        Ok(String::from(
+            "1606938044258990275541962092341162602522202993782792835301376"
+        ))
+    );
+    assert_eq!(
+        eval::<BigInt>("(x = 4611686018427387904) + x(4)").map(|n| n.to_string()),
+        Ok(String::from("23058430092136939520"))
+    );
+    assert_eq!(eval::<BigInt>("2^(2^30)"), overflow("^"));
+    // the factorial stops at 10000 instead of multiplying for hours
+    assert_eq!(eval::<BigInt>("factorial(10^12)"), overflow("factorial"));
+}
@@ -0,0 +1,114 @@
+use rscalc::{parse, tokenize, InterpretError, Interpreter, Interval, OpVal, TokenValue};
+use std::cmp::Ordering;
+
+fn try_eval(input: &str) -> Result<Interval, InterpretError<'static>> {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    Interpreter::default()
+        .eval(&expr)
+        .map_err(InterpretError::into_owned)
+}
+
+fn eval(input: &str) -> Interval {
+    try_eval(input).unwrap()
+}
+
+// Whether lo * factor - target <= 0 <= hi * factor - target, with the sign computed exactly
+fn brackets_product(interval: Interval, factor: f64, target: f64) -> bool {
+    interval.lo.mul_add(factor, -target) <= 0.0 && interval.hi.mul_add(factor, -target) >= 0.0
+}
+
+#[test]
+fn exact_results_stay_points() {
+    assert_eq!(eval("1 + 2 * 3"), Interval::point(7.0));
+    assert_eq!(eval("2^10 - 24"), Interval::point(1000.0));
+    assert_eq!(eval("3 / 4 * 4"), Interval::point(3.0));
+}
+
+#[test]
+fn rounded_results_are_widened_outwards() {
+    let third = eval("1 / 3");
+    assert!(third.lo < third.hi);
+    assert_eq!(third.lo.next_up(), third.hi);
+    assert!(brackets_product(third, 3.0, 1.0));
+
+    let root = eval("sqrt(2)");
+    assert!(root.lo < root.hi);
+    assert!(root.lo.mul_add(root.lo, -2.0) <= 0.0 && root.hi.mul_add(root.hi, -2.0) >= 0.0);
+
+    // 0.1 is not exact, so neither are ten of them, but 1 is still inside
+    let sum = eval("0.1 + 0.1 + 0.1 + 0.1 + 0.1 + 0.1 + 0.1 + 0.1 + 0.1 + 0.1");
+    assert!(sum.contains(1.0) && !sum.is_point());
+}
+
+#[test]
+fn functions_contain_every_value_over_the_interval() {
+    assert_eq!(eval("sin([0, 2])").hi, 1.0);
+    assert_eq!(eval("cos([-1, 4])"), Interval::new(-1.0, 1.0));
+    let squared = eval("[-2, 3]^2");
+    assert_eq!((squared.lo, squared.hi), (0.0, 9.0));
+    let exp = eval("exp([0, 1])");
+    assert!(exp.contains(1.0) && exp.contains(std::f64::consts::E));
+    // the interpreter reports it as an error, the operator has no bounds
+    assert!(try_eval("1 / [-1, 1]").is_err());
+    assert_eq!(
+        Interval::point(1.0) / Interval::new(-1.0, 1.0),
+        Interval::ENTIRE
+    );
+}
+
+#[test]
+fn overlapping_intervals_are_unordered() {
+    let (low, high) = (Interval::new(1.0, 2.0), Interval::new(3.0, 4.0));
+    assert_eq!(low.partial_cmp(&high), Some(Ordering::Less));
+    assert_eq!(high.partial_cmp(&low), Some(Ordering::Greater));
+    assert_eq!(low.partial_cmp(&Interval::new(2.0, 3.0)), None);
+}
+
+#[test]
+fn bounds_and_errors_parse_as_one_number() {
+    assert_eq!("[1, 2]".parse(), Ok(Interval::new(1.0, 2.0)));
+    assert_eq!("3".parse(), Ok(Interval::point(3.0)));
+    assert!("[2, 1]".parse::<Interval>().is_err());
+    assert!("[1 2]".parse::<Interval>().is_err());
+    assert!("5±-1".parse::<Interval>().is_err());
+
+    let uncertain: Interval = "5±0.1".parse().unwrap();
+    assert!(uncertain.lo < 4.9 && uncertain.hi > 5.1);
+    // 0.1 is rounded when parsed, so it becomes the interval around it
+    let tenth: Interval = "0.1".parse().unwrap();
+    assert!(tenth.contains(0.1) && !tenth.is_point());
+}
+
+#[test]
+fn inexact_bounds_are_widened_outwards() {
+    // 0.1 and 0.2 are rounded when parsed, so the bounds move past them
+    let bounds: Interval = "[0.1, 0.2]".parse().unwrap();
+    assert!(bounds.contains(0.1) && bounds.contains(0.2));
+    assert!(bounds.lo < 0.1 && bounds.hi > 0.2);
+    // whole numbers above 2^53 are rounded too
+    let avogadro: Interval = "6.022e23".parse().unwrap();
+    assert!(avogadro.contains(6.022e23) && !avogadro.is_point());
+    assert_eq!(
+        "9007199254740992".parse(),
+        Ok(Interval::point(2f64.powi(53)))
+    );
+}
+
+#[test]
+fn tokenizer_reads_bounds_and_errors() {
+    let tokens = tokenize::<Interval>("[1, 2] + 5 ± 0.5").unwrap();
+    let values: Vec<_> = tokens.iter().map(|token| token.value.clone()).collect();
+    assert_eq!(
+        values,
+        vec![
+            TokenValue::Num(Interval::new(1.0, 2.0)),
+            TokenValue::Op(OpVal::Add),
+            TokenValue::Num(Interval::new(4.5f64.next_down(), 5.5f64.next_up())),
+        ]
+    );
+    assert_eq!(tokens[2].span, 9..17);
+    assert!(tokenize::<Interval>("[1, 2").is_err());
+    // other types reject the bounds instead of splitting them up
+    assert!(tokenize::<f64>("[1, 2]").is_err());
+}
@@ -0,0 +1,55 @@
+use rscalc::{parse, tokenize, InterpretError, Interpreter};
+
+fn eval(input: &str) -> Result<f64, InterpretError<'static>> {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    Interpreter::default()
+        .eval(&expr)
+        .map_err(InterpretError::into_owned)
+}
+
+#[test]
+fn lazy_functions_only_evaluate_what_they_use() {
+    assert_eq!(eval("sigma(i, 1, 10, i^2)"), Ok(385.0));
+    assert_eq!(eval("2 * sigma(i, 1, 3, i) + 1"), Ok(13.0));
+    assert!((eval("integrate(x^2, x, 0, 3)").unwrap() - 9.0).abs() < 1e-9);
+}
+
+#[test]
+fn sum_adds_up_its_arguments_however_they_are_written() {
+    let mut interpreter = Interpreter::<f64>::default();
+    let mut eval_line = |input: &str| {
+        let tokens = tokenize(input).unwrap();
+        let expr = parse(&tokens).unwrap();
+        interpreter.eval(&expr).unwrap()
+    };
+    for input in ["a = 1", "b = 2", "c = 3", "d = 4"] {
+        eval_line(input);
+    }
+    assert_eq!(eval_line("sum(a, b, c, d)"), 10.0);
+    assert_eq!(eval_line("sum(1, b, c, d)"), 10.0);
+    assert_eq!(eval("sum(1, 2, 3, 4, 5)"), Ok(15.0));
+    assert_eq!(eval("sum()"), Ok(0.0));
+}
+
+#[test]
+fn sigma_steps_a_name_from_a_to_b() {
+    assert_eq!(eval("sigma(i, 1, 10, 5)"), Ok(50.0));
+    assert_eq!(eval("sigma(i, 3, 1, i)"), Ok(0.0));
+    assert_eq!(
+        eval("sigma(1, 2, 3, 4)"),
+        Err(InterpretError::DomainError("sigma".into()))
+    );
+    assert_eq!(
+        eval("sigma(i, 1, 3)"),
+        Err(InterpretError::TooFewArgs("sigma".into(), 4))
+    );
+}
+
+#[test]
+fn sigmas_with_too_many_terms_overflow() {
+    assert_eq!(
+        eval("sigma(i, 1, 1e7, i)"),
+        Err(InterpretError::Overflow("sigma".into()))
+    );
+}
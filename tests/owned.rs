@@ -0,0 +1,28 @@
+use rscalc::{parse, tokenize, Expr, Interpreter, Variant};
+use std::thread;
+
+struct Formula {
+    expr: Expr<f64>,
+}
+
+fn compile(input: String) -> Formula {
+    let tokens = tokenize(&input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    // the input and tokens are dropped here, but the expression lives on
+    Formula { expr }
+}
+
+#[test]
+fn parsed_expressions_outlive_their_input() {
+    let formula = compile(String::from("x^2 + 2x + 1"));
+    let handle = thread::spawn(move || {
+        let mut interpreter = Interpreter::<f64>::default();
+        (0..4)
+            .map(|x| {
+                interpreter.set_var(String::from("x"), Variant::Num(f64::from(x)));
+                interpreter.eval(&formula.expr).unwrap()
+            })
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(handle.join().unwrap(), [1.0, 4.0, 9.0, 16.0]);
+}
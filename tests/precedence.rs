@@ -0,0 +1,148 @@
+use rscalc::{parse, tokenize, Expr, Interpreter, OpVal, Quantity};
+
+fn eval(input: &str) -> f64 {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    Interpreter::default().eval(&expr).unwrap()
+}
+
+fn num(n: f64) -> Box<Expr<f64>> {
+    Box::new(Expr::Num(n))
+}
+
+#[test]
+fn pow_is_right_associative() {
+    assert_eq!(eval("2^3^2"), 512.0);
+    assert_eq!(eval("(2^3)^2"), 64.0);
+    assert_eq!(eval("2^1^3^2"), 2.0);
+
+    let tokens = tokenize::<f64>("2^3^2").unwrap();
+    assert_eq!(
+        parse(&tokens).unwrap(),
+        Expr::Op(
+            OpVal::Pow,
+            num(2.0),
+            Box::new(Expr::Op(OpVal::Pow, num(3.0), num(2.0), 3..4)),
+            1..2
+        )
+    );
+}
+
+#[test]
+fn unary_minus_binds_looser_than_pow() {
+    assert_eq!(eval("-3^2"), -9.0);
+    assert_eq!(eval("(-3)^2"), 9.0);
+    assert_eq!(eval("-2^-2"), -0.25);
+    assert_eq!(eval("2^-1"), 0.5);
+    assert_eq!(eval("2^-1^2"), 0.5);
+
+    let tokens = tokenize::<f64>("-x^2").unwrap();
+    assert_eq!(
+        parse(&tokens).unwrap(),
+        Expr::Neg(
+            Box::new(Expr::Op(
+                OpVal::Pow,
+                Box::new(Expr::Var(String::from("x"), 1..2)),
+                num(2.0),
+                2..3
+            )),
+            0..1
+        )
+    );
+}
+
+#[test]
+fn unary_minus_binds_tighter_than_add_and_mul() {
+    assert_eq!(eval("-2 + 3"), 1.0);
+    assert_eq!(eval("-2 * 3 + 1"), -5.0);
+    assert_eq!(eval("2 * -3"), -6.0);
+    assert_eq!(eval("1 - -1"), 2.0);
+    assert_eq!(eval("--2"), 2.0);
+    assert_eq!(eval("-(2 + 3)"), -5.0);
+}
+
+#[test]
+fn mul_binds_tighter_than_add() {
+    assert_eq!(eval("2 + 3 * 4"), 14.0);
+    assert_eq!(eval("2 * 3 + 4"), 10.0);
+    assert_eq!(eval("(2 + 3) * 4"), 20.0);
+    assert_eq!(eval("10 - 6 / 2"), 7.0);
+    assert_eq!(eval("1 + 7 % 4"), 4.0);
+}
+
+#[test]
+fn pow_binds_tighter_than_mul() {
+    assert_eq!(eval("2 * 3^2"), 18.0);
+    assert_eq!(eval("2^3 * 2"), 16.0);
+    assert_eq!(eval("64 / 2^3"), 8.0);
+}
+
+#[test]
+fn add_and_mul_are_left_associative() {
+    assert_eq!(eval("10 - 4 - 3"), 3.0);
+    assert_eq!(eval("24 / 4 / 2"), 3.0);
+    assert_eq!(eval("7 % 4 * 2"), 6.0);
+}
+
+#[test]
+fn factorial_binds_tightest() {
+    assert_eq!(eval("3!^2"), 36.0);
+    assert_eq!(eval("-3!"), -6.0);
+    assert_eq!(eval("2^3!"), 64.0);
+    assert_eq!(eval("|-2|^2"), 4.0);
+}
+
+#[test]
+fn number_times_identifier_binds_tighter_than_mul_but_looser_than_pow() {
+    use std::f64::consts::PI;
+    assert_eq!(eval("2 pi"), 2.0 * PI);
+    assert_eq!(eval("2 pi^2"), 2.0 * PI * PI);
+    assert_eq!(eval("1 / 2 pi"), 1.0 / (2.0 * PI));
+    assert_eq!(eval("-2 pi"), -2.0 * PI);
+}
+
+#[test]
+fn conversion_binds_looser_than_add() {
+    let eval = |input| {
+        let tokens = tokenize::<Quantity>(input).unwrap();
+        let expr = parse(&tokens).unwrap();
+        Interpreter::default().eval(&expr).unwrap().to_string()
+    };
+    assert_eq!(eval("1 km + 500 m in m"), "1500 m");
+    assert_eq!(eval("2 km * 3 in m"), "6000 m");
+    assert_eq!(eval("x = 3 km to m"), "3000 m");
+}
+
+#[test]
+fn comparison_binds_looser_than_add_and_tighter_than_logic() {
+    assert_eq!(eval("1 + 1 == 2"), 1.0);
+    assert_eq!(eval("2 * 3 < 5"), 0.0);
+    assert_eq!(eval("1 < 2 && 3 > 4"), 0.0);
+    assert_eq!(eval("1 < 2 || 3 > 4 && 0"), 1.0);
+    assert_eq!(eval("0 && 1 || 1"), 1.0);
+    assert_eq!(eval("x = 2 >= 2"), 1.0);
+}
+
+#[test]
+fn comparisons_are_left_associative() {
+    // ((2 < 1) == 0) < 5, not (2 < 1) == (0 < 5)
+    assert_eq!(eval("2 < 1 == 0 < 5"), 1.0);
+    assert_eq!(eval("3 > 2 > 1"), 0.0);
+}
+
+#[test]
+fn double_pipes_are_or_only_between_operands() {
+    assert_eq!(eval("||-3| - 1|"), 2.0);
+    assert_eq!(eval("||-3|-1|"), 2.0);
+    assert_eq!(eval("(||-2||)"), 2.0);
+    assert_eq!(eval("|1 - |3||"), 2.0);
+    assert_eq!(eval("0 || |-1|"), 1.0);
+    assert_eq!(eval("0 ||0"), 0.0);
+}
+
+#[test]
+fn logic_short_circuits() {
+    assert_eq!(eval("0 && 1 / 0"), 0.0);
+    assert_eq!(eval("1 || undefined"), 1.0);
+    assert_eq!(eval("2 && 3"), 1.0);
+}
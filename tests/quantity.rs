@@ -0,0 +1,86 @@
+use rscalc::{parse, tokenize, InterpretError, Interpreter, OpVal, Quantity, TokenValue};
+
+fn eval(input: &str) -> Result<String, InterpretError<'static>> {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    Interpreter::<Quantity>::default()
+        .eval(&expr)
+        .map(|q| q.to_string())
+        .map_err(InterpretError::into_owned)
+}
+
+#[test]
+fn conversions_keep_the_target_unit() {
+    assert_eq!(eval("10 km in mi"), Ok("6.2137119223733395 mi".into()));
+    assert_eq!(eval("1 h to s"), Ok("3600 s".into()));
+    assert_eq!(eval("5 km + 500 m"), Ok("5500 m".into()));
+}
+
+#[test]
+fn mismatched_units_are_errors() {
+    assert_eq!(
+        eval("5 m + 3 s"),
+        Err(InterpretError::DimensionMismatch("+".into()))
+    );
+    assert_eq!(
+        eval("5 m in s"),
+        Err(InterpretError::DimensionMismatch("in".into()))
+    );
+}
+
+#[test]
+fn unit_powers_that_overflow_are_errors() {
+    assert_eq!(
+        eval("m^(2^30) * m^(2^30)"),
+        Err(InterpretError::Overflow("*".into()))
+    );
+    assert_eq!(
+        eval("m^(2^30) / m^(-(2^30))"),
+        Err(InterpretError::Overflow("/".into()))
+    );
+}
+
+#[test]
+fn in_and_to_are_names_unless_they_follow_a_value() {
+    let mut interpreter = Interpreter::<Quantity>::default();
+    let mut eval_line = |input: &str| {
+        let tokens = tokenize(input).unwrap();
+        let expr = parse(&tokens).unwrap();
+        interpreter
+            .eval(&expr)
+            .map(|q| q.to_string())
+            .map_err(InterpretError::into_owned)
+    };
+    eval_line("in = 2").unwrap();
+    eval_line("to = 3").unwrap();
+    assert_eq!(eval_line("in * to"), Ok("6".into()));
+    // the value converted to has to be exactly one of a unit
+    assert_eq!(
+        eval_line("12 in in"),
+        Err(InterpretError::DomainError("in".into()))
+    );
+
+    let values = |input| {
+        tokenize::<Quantity>(input)
+            .unwrap()
+            .into_iter()
+            .map(|token| match token.value {
+                TokenValue::Op(OpVal::In) => "convert",
+                TokenValue::Id(id) => id,
+                _ => "",
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(values("in in to"), ["in", "convert", "to"]);
+    assert_eq!(
+        values("(1) to |x| in x"),
+        ["", "", "", "convert", "", "x", "", "convert", "x"]
+    );
+}
+
+#[test]
+fn repeated_units_are_merged_into_powers() {
+    assert_eq!(eval("2 km * 3 km"), Ok("6 km^2".into()));
+    assert_eq!(eval("km^2 * km"), Ok("1 km^3".into()));
+    assert_eq!(eval("km * m"), Ok("1 km*m".into()));
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "rational")]
+
+use rscalc::{parse, tokenize, InterpretError, Interpreter, Num, Rational};
+
+fn eval(input: &str) -> Result<Rational, InterpretError<'static>> {
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    Interpreter::default()
+        .eval(&expr)
+        .map_err(InterpretError::into_owned)
+}
+
+fn eval_text(input: &str) -> String {
+    eval(input).unwrap().to_string()
+}
+
+#[test]
+fn arithmetic_is_exact() {
+    assert_eq!(eval_text("1/3 + 1/6"), "1/2");
+    assert_eq!(eval_text("0.1 + 0.2"), "3/10");
+    assert_eq!(eval_text("1.5e-3"), "3/2000");
+    assert_eq!(eval_text("(2/3)^-2"), "9/4");
+    assert_eq!(eval_text("8^(2/3)"), "4");
+    assert_eq!(eval_text("7 % (5/2)"), "2");
+    assert!(eval("6/3").unwrap().is_whole());
+    assert!(!eval("7/3").unwrap().is_whole());
+}
+
+#[test]
+fn fractions_parse_and_print_as_decimals() {
+    assert_eq!("-1/3".parse::<Rational>().unwrap().to_string(), "-1/3");
+    assert!("1/0".parse::<Rational>().is_err());
+    let third = eval("1/3").unwrap();
+    assert_eq!(third.to_decimal(4), (String::from("0.3333"), false));
+    assert_eq!(
+        eval("-5/4").unwrap().to_decimal(4),
+        (String::from("-1.25"), true)
+    );
+}
+
+#[test]
+fn inexact_functions_fall_back_to_floats() {
+    assert_eq!(eval("2^(1/2)").unwrap().to_f64(), 2f64.sqrt());
+    assert_eq!(eval("ln(1)"), Ok(Rational::zero()));
+    assert_eq!(eval("ln(0)"), Err(InterpretError::DomainError("ln".into())));
+}
+
+#[test]
+fn huge_results_overflow_instead_of_exhausting_memory() {
+    assert_eq!(eval("3^(2^30)"), Err(InterpretError::Overflow("^".into())));
+    assert_eq!(
+        eval("factorial(10001)"),
+        Err(InterpretError::Overflow("factorial".into()))
+    );
+    assert_eq!(eval("1 / 0"), Err(InterpretError::DivisionByZero));
+}
+
+#[test]
+fn equations_are_solved_with_bounded_denominators() {
+    assert_eq!(eval_text("x^3 = 8"), "2");
+    assert_eq!(eval_text("2x + 1/2 = 3"), "5/4");
+    // these never converged while every step grew the denominator
+    let cube_root = eval("x^3 = 2").unwrap();
+    assert!((cube_root.to_f64() - 2f64.cbrt()).abs() < 1e-15);
+    assert!(cube_root.denom().bits() <= 65);
+    let log = eval("2^x = 3").unwrap();
+    assert!((log.to_f64() - 3f64.log2()).abs() < 1e-12);
+}
@@ -0,0 +1,78 @@
+use rscalc::{parse, to_latex, to_mathml, tokenize, Expr};
+
+fn parse_str(input: &str) -> Expr<f64> {
+    let tokens = tokenize(input).unwrap();
+    parse(&tokens).unwrap()
+}
+
+fn latex(input: &str) -> String {
+    to_latex(&parse_str(input))
+}
+
+#[test]
+fn latex_uses_math_notation_for_builtins() {
+    assert_eq!(latex("sqrt(x) / 2"), r"\frac{\sqrt{x}}{2}");
+    assert_eq!(
+        latex("|x - 1| + cbrt(y)"),
+        r"\left|x - 1\right| + \sqrt[3]{y}"
+    );
+    assert_eq!(latex("2x^(n + 1)"), r"2 \cdot x^{n + 1}");
+    assert_eq!(latex("3! * 90°"), r"3! \cdot 90^\circ");
+    assert_eq!(
+        latex("asin(theta) >= log(x, 2)"),
+        r"\arcsin\left(\theta\right) \geq \log_{2}\left(x\right)"
+    );
+    assert_eq!(
+        latex("speed(t) = t < 0 ? 0 : t"),
+        r"\operatorname{speed}\left(t\right) = \begin{cases} 0 & \text{if } t < 0 \\ t & \text{otherwise} \end{cases}"
+    );
+}
+
+#[test]
+fn latex_only_adds_needed_parentheses() {
+    assert_eq!(latex("(a + b) / (c - d)"), r"\frac{a + b}{c - d}");
+    assert_eq!(latex("(a / b)^2"), r"\left(\frac{a}{b}\right)^{2}");
+    assert_eq!(latex("-(x + 1) * y"), r"-\left(x + 1\right) \cdot y");
+    assert_eq!(latex("a - (b - c)"), r"a - \left(b - c\right)");
+}
+
+#[test]
+fn mathml_is_one_math_element() {
+    assert_eq!(
+        to_mathml(&parse_str("x^2 / |y|")),
+        concat!(
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML">"#,
+            "<mfrac><msup><mi>x</mi><mn>2</mn></msup><mrow><mo>|</mo><mi>y</mi><mo>|</mo></mrow></mfrac>",
+            "</math>"
+        )
+    );
+    assert_eq!(
+        to_mathml(&parse_str("(a - b) * sin(pi) < 1")),
+        concat!(
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML"><mrow><mrow>"#,
+            "<mrow><mo>(</mo><mrow><mi>a</mi><mo>−</mo><mi>b</mi></mrow><mo>)</mo></mrow><mo>⋅</mo>",
+            "<mrow><mi>sin</mi><mo>&#x2061;</mo><mrow><mo>(</mo><mi>π</mi><mo>)</mo></mrow></mrow>",
+            "</mrow><mo>&lt;</mo><mn>1</mn></mrow></math>"
+        )
+    );
+}
+
+#[test]
+fn negated_negatives_are_parenthesized() {
+    let expr = Expr::Neg(Box::new(Expr::Num(-3.0)), 0..1);
+    assert_eq!(to_latex(&expr), r"-\left(-3\right)");
+    assert_eq!(
+        to_mathml(&expr),
+        concat!(
+            r#"<math xmlns="http://www.w3.org/1998/Math/MathML"><mrow><mo>−</mo>"#,
+            "<mrow><mo>(</mo><mrow><mo>−</mo><mn>3</mn></mrow><mo>)</mo></mrow></mrow></math>"
+        )
+    );
+    assert_eq!(latex("--x"), r"-\left(-x\right)");
+    assert_eq!(latex("-x^2"), r"-x^{2}");
+}
+
+#[test]
+fn omicron_is_a_latin_o_in_latex() {
+    assert_eq!(latex("omicron + alpha"), r"o + \alpha");
+}
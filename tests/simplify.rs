@@ -0,0 +1,66 @@
+use rscalc::{parse, simplify, tokenize, Expr, Interpreter, Variant};
+
+fn parse_str(input: &str) -> Expr<f64> {
+    let tokens = tokenize(input).unwrap();
+    parse(&tokens).unwrap()
+}
+
+fn assert_simplifies_to(input: &str, expected: &str) {
+    assert_eq!(
+        simplify(&parse_str(input)),
+        parse_str(expected),
+        "{}",
+        input
+    );
+}
+
+#[test]
+fn constants_are_folded_and_identities_removed() {
+    assert_simplifies_to("x*1 + 0 + 2*3", "x + 6");
+    assert_simplifies_to("x / 1 + y^0 + z^1", "x + z + 1");
+    assert_simplifies_to("2 * x * 3", "6 * x");
+    assert_simplifies_to("1 < 2 ? y : z", "y");
+    assert_simplifies_to("0 && q", "0");
+    // folding would hide the error the interpreter reports
+    assert_simplifies_to("1 / 0 + x", "1 / 0 + x");
+}
+
+#[test]
+fn like_terms_are_combined_and_negation_normalized() {
+    assert_simplifies_to("2x + 3x - x", "4 * x");
+    assert_simplifies_to("x + y - x", "y");
+    assert_simplifies_to("x - x", "0");
+    assert_simplifies_to("-(-x)", "x");
+    assert_simplifies_to("-x * 3 + 4x", "x");
+    assert_simplifies_to("-(x - 3)", "-x + 3");
+    assert_simplifies_to("sin(x) * 2 + sin(x)", "3 * sin(x)");
+}
+
+#[test]
+fn simplified_expressions_evaluate_the_same() {
+    let mut interpreter = Interpreter::<f64>::default();
+    interpreter.set_var(String::from("y"), Variant::Num(0.25));
+    for input in [
+        "x*1 + 0 + 2*3",
+        "(x - 2) * 3 - x / 2 + x^2",
+        "-(x + y) * -2 + 3 * (y - x)",
+        "x < 1 ? 2x - x : -x",
+        "sqrt(x + x + 4) * 2 - sqrt(2x + 4) + y^1",
+    ] {
+        let expr = parse_str(input);
+        let simplified = simplify(&expr);
+        for x in [-1.5, 0.5, 3.0] {
+            interpreter.set_var(String::from("x"), Variant::Num(x));
+            let expected = interpreter.eval(&expr);
+            let actual = interpreter.eval(&simplified);
+            match (expected, actual) {
+                (Ok(expected), Ok(actual)) => {
+                    assert!((expected - actual).abs() < 1e-12, "{} at {}", input, x)
+                }
+                (expected, actual) => {
+                    assert_eq!(expected.is_err(), actual.is_err(), "{} at {}", input, x)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use rscalc::{parse, tokenize, InterpretError, Interpreter, Num, Variant};
+
+fn solve<N: Num>(input: &str) -> Result<N, InterpretError<'static>>
+where
+    Interpreter<N>: Default,
+{
+    let tokens = tokenize(input).unwrap();
+    let expr = parse(&tokens).unwrap();
+    Interpreter::default()
+        .eval(&expr)
+        .map_err(InterpretError::into_owned)
+}
+
+fn assert_close(input: &str, expected: f64) {
+    let actual = solve::<f64>(input).unwrap();
+    assert!(
+        (actual - expected).abs() < 1e-9 * expected.abs().max(1.0),
+        "{}: {} != {}",
+        input,
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn polynomials_are_solved_exactly_or_iteratively() {
+    assert_eq!(solve::<f64>("2*y + 3 = 11"), Ok(4.0));
+    assert_eq!(solve::<f64>("2x + 3 = 11"), Ok(4.0));
+    assert_eq!(solve::<f64>("3 = x / 4"), Ok(12.0));
+    assert_eq!(solve::<f64>("x^2 - 5x + 6 = 0"), Ok(3.0));
+    assert_close("x^3 = 2", 2f64.cbrt());
+    assert_close("x^5 = 10^20", 1e4);
+    assert_close("x^3 = 10^30", 1e10);
+}
+
+#[test]
+fn newton_tries_other_starts_where_the_derivative_vanishes() {
+    // the derivative of x^3 - 3x is 0 at x = 1, where the first attempt starts
+    assert_close("x^3 - 3*x = 5", 2.279_018_786_166_593);
+    assert_close("x^3 + 20 = 0", -(20f64.cbrt()));
+}
+
+#[test]
+fn other_equations_are_solved_numerically() {
+    assert_close("2^x = 1000", 1000f64.log2());
+    assert_close("ln(x) = 2", 2f64.exp());
+    assert_close("x * e^x = 1", 0.567_143_290_409_783_8);
+    // the root closest to the start, not one of the many others
+    assert_close("sin(x) = 0.5", std::f64::consts::FRAC_PI_6);
+}
+
+#[test]
+fn roots_are_accepted_relative_to_the_size_of_the_terms() {
+    // x^4 is only accurate to about 1e8 here, which an absolute tolerance rejects
+    assert_close("x^4 = 10^24", 1e6);
+}
+
+#[test]
+fn builtins_called_with_an_unknown_are_solved_instead_of_redefined() {
+    assert_close("sqrt(x) = 3", 9.0);
+
+    let mut interpreter = Interpreter::<f64>::default();
+    let mut eval = |input| {
+        let tokens = tokenize(input).unwrap();
+        let expr = parse(&tokens).unwrap();
+        interpreter.eval(&expr).map_err(InterpretError::into_owned)
+    };
+    eval("sqrt(x) = 3").unwrap();
+    assert_eq!(eval("sqrt(16)"), Ok(4.0));
+    // user functions can still be redefined
+    eval("f(x) = x + 1").unwrap();
+    eval("f(y) = y * 2").unwrap();
+    assert_eq!(eval("f(3)"), Ok(6.0));
+}
+
+#[test]
+fn equations_without_a_single_solution_are_errors() {
+    assert_eq!(
+        solve::<f64>("x^4 + 1 = 0"),
+        Err(InterpretError::NoSolution("x".into()))
+    );
+    assert_eq!(
+        solve::<f64>("x + 1 = x"),
+        Err(InterpretError::NoSolution("x".into()))
+    );
+    assert_eq!(
+        solve::<f64>("2x = x + x"),
+        Err(InterpretError::InfiniteSolutions("x".into()))
+    );
+    assert_eq!(
+        solve::<f64>("x + y = 1"),
+        Err(InterpretError::MultipleUnknowns("x".into(), "y".into()))
+    );
+    assert_eq!(solve::<f64>("1 = 1"), Err(InterpretError::NoUnknown));
+}
+
+#[test]
+fn integer_roots_must_be_exact() {
+    assert_eq!(solve::<i64>("x^3 = 27"), Ok(3));
+    assert_eq!(solve::<i64>("x^3 = -27"), Ok(-3));
+    assert_eq!(
+        solve::<i64>("x^2 = 2"),
+        Err(InterpretError::NoSolution("x".into()))
+    );
+    assert_eq!(
+        solve::<i64>("2x = 3"),
+        Err(InterpretError::NoSolution("x".into()))
+    );
+}
+
+#[test]
+fn solved_variables_are_bound() {
+    let tokens = tokenize::<f64>("x^2 = 9").unwrap();
+    let expr = parse(&tokens).unwrap();
+    let mut interpreter = Interpreter::default();
+    assert_eq!(interpreter.eval(&expr), Ok(3.0));
+    assert!(matches!(interpreter.vars.get("x"), Some(Variant::Num(x)) if *x == 3.0));
+}
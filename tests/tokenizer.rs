@@ -0,0 +1,197 @@
+use rscalc::{
+    tokenize, Num, OpVal, SymbolVal, Token, TokenValue, TokenizeError, TokenizeErrorCode,
+};
+use std::ops::Range;
+
+fn token<N: Num>(value: TokenValue<'_, N>, span: Range<usize>) -> Token<'_, N> {
+    Token { value, span }
+}
+
+#[test]
+fn numbers_take_exponents_and_separators() {
+    assert_eq!(
+        tokenize::<f64>("6.022e23"),
+        Ok(vec![token(TokenValue::Num(6.022e23), 0..8)])
+    );
+    assert_eq!(
+        tokenize::<f64>("1E-9 + 2.5e+3"),
+        Ok(vec![
+            token(TokenValue::Num(1e-9), 0..4),
+            token(TokenValue::Op(OpVal::Add), 5..6),
+            token(TokenValue::Num(2500.0), 7..13),
+        ])
+    );
+    assert_eq!(
+        tokenize::<f64>("1_000_000.5"),
+        Ok(vec![token(TokenValue::Num(1_000_000.5), 0..11)])
+    );
+    assert_eq!(
+        tokenize::<f64>("1e1_0"),
+        Ok(vec![token(TokenValue::Num(1e10), 0..5)])
+    );
+}
+
+#[test]
+fn incomplete_exponents_and_separators_are_identifiers() {
+    // 2e is 2 times e, and a separator must sit between two digits
+    assert_eq!(
+        tokenize::<f64>("2e"),
+        Ok(vec![
+            token(TokenValue::Num(2.0), 0..1),
+            token(TokenValue::Id("e"), 1..2),
+        ])
+    );
+    assert_eq!(
+        tokenize::<f64>("2e-x"),
+        Ok(vec![
+            token(TokenValue::Num(2.0), 0..1),
+            token(TokenValue::Id("e"), 1..2),
+            token(TokenValue::Op(OpVal::Sub), 2..3),
+            token(TokenValue::Id("x"), 3..4),
+        ])
+    );
+    assert_eq!(
+        tokenize::<f64>("1_"),
+        Ok(vec![
+            token(TokenValue::Num(1.0), 0..1),
+            token(TokenValue::Id("_"), 1..2),
+        ])
+    );
+}
+
+#[test]
+fn integers_expand_whole_exponents() {
+    assert_eq!(
+        tokenize::<i64>("1.5e3"),
+        Ok(vec![token(TokenValue::Num(1500), 0..5)])
+    );
+    assert_eq!(
+        tokenize::<i64>("1e-3"),
+        Err(TokenizeError {
+            code: TokenizeErrorCode::InvalidNumber("1e-3"),
+            span: 0..4,
+        })
+    );
+    assert_eq!(
+        tokenize::<i64>("1e100000"),
+        Err(TokenizeError {
+            code: TokenizeErrorCode::InvalidNumber("1e100000"),
+            span: 0..8,
+        })
+    );
+}
+
+#[test]
+fn errors_point_at_the_invalid_text() {
+    assert_eq!(
+        tokenize::<f64>("1 + 2..3"),
+        Err(TokenizeError {
+            code: TokenizeErrorCode::InvalidNumber("2..3"),
+            span: 4..8,
+        })
+    );
+    assert_eq!(
+        tokenize::<f64>("2 # 3"),
+        Err(TokenizeError {
+            code: TokenizeErrorCode::UnrecognizedChar('#'),
+            span: 2..3,
+        })
+    );
+    // spans are byte offsets, so they stay correct after multibyte characters
+    assert_eq!(
+        tokenize::<f64>("90° $"),
+        Err(TokenizeError {
+            code: TokenizeErrorCode::UnrecognizedChar('$'),
+            span: 5..6,
+        })
+    );
+}
+
+#[test]
+fn conversions_need_a_type_with_units() {
+    assert_eq!(
+        tokenize::<f64>("12 in 4"),
+        Err(TokenizeError {
+            code: TokenizeErrorCode::NoUnits("in"),
+            span: 3..5,
+        })
+    );
+    assert_eq!(
+        tokenize::<i64>("(1) to 2"),
+        Err(TokenizeError {
+            code: TokenizeErrorCode::NoUnits("to"),
+            span: 4..6,
+        })
+    );
+    // anywhere else they are still names
+    assert_eq!(
+        tokenize::<f64>("in = 2"),
+        Ok(vec![
+            token(TokenValue::Id("in"), 0..2),
+            token(TokenValue::Op(OpVal::Eq), 3..4),
+            token(TokenValue::Num(2.0), 5..6),
+        ])
+    );
+}
+
+#[test]
+fn prefixed_integers_are_hex_binary_or_octal() {
+    assert_eq!(
+        tokenize::<f64>("0xFF + 0b1011 * 0o777"),
+        Ok(vec![
+            token(TokenValue::Num(255.0), 0..4),
+            token(TokenValue::Op(OpVal::Add), 5..6),
+            token(TokenValue::Num(11.0), 7..13),
+            token(TokenValue::Op(OpVal::Mul), 14..15),
+            token(TokenValue::Num(511.0), 16..21),
+        ])
+    );
+    assert_eq!(
+        tokenize::<f64>("0xdead_beef"),
+        Ok(vec![token(TokenValue::Num(3_735_928_559.0), 0..11)])
+    );
+    // digits outside the radix end the literal, and a prefix without digits is 0 times an identifier
+    assert_eq!(
+        tokenize::<f64>("0b12"),
+        Ok(vec![
+            token(TokenValue::Num(1.0), 0..3),
+            token(TokenValue::Num(2.0), 3..4),
+        ])
+    );
+    assert_eq!(
+        tokenize::<f64>("0xg"),
+        Ok(vec![
+            token(TokenValue::Num(0.0), 0..1),
+            token(TokenValue::Id("xg"), 1..3),
+        ])
+    );
+    assert_eq!(
+        tokenize::<i64>("0xffff_ffff_ffff_ffff"),
+        Err(TokenizeError {
+            code: TokenizeErrorCode::InvalidNumber("0xffff_ffff_ffff_ffff"),
+            span: 0..21,
+        })
+    );
+}
+
+#[test]
+fn double_pipes_split_unless_they_join_two_operands() {
+    assert_eq!(
+        tokenize::<f64>("x || y"),
+        Ok(vec![
+            token(TokenValue::Id("x"), 0..1),
+            token(TokenValue::Op(OpVal::Or), 2..4),
+            token(TokenValue::Id("y"), 5..6),
+        ])
+    );
+    assert_eq!(
+        tokenize::<f64>("||x||"),
+        Ok(vec![
+            token(TokenValue::Symbol(SymbolVal::Pipe), 0..1),
+            token(TokenValue::Symbol(SymbolVal::Pipe), 1..2),
+            token(TokenValue::Id("x"), 2..3),
+            token(TokenValue::Symbol(SymbolVal::Pipe), 3..4),
+            token(TokenValue::Symbol(SymbolVal::Pipe), 4..5),
+        ])
+    );
+}